@@ -0,0 +1,72 @@
+use assassin::quote::Quote;
+use assassin::traits::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Assumes the holder of an in-the-money option exercises it as soon as there's
+// little time value left to give up, or (for calls) when the dividend they'd
+// collect by owning the shares is worth more than the time value.
+#[allow(dead_code)]
+pub struct ExtrinsicValueAssignment {
+    threshold: Money,
+}
+
+impl ExtrinsicValueAssignment {
+    #[allow(dead_code)]
+    pub fn new(threshold: Money) -> ExtrinsicValueAssignment {
+        if threshold < Money::zero() {
+            panic!("threshold must be >= 0.0 (got {})", threshold);
+        }
+
+        ExtrinsicValueAssignment {
+            threshold: threshold,
+        }
+    }
+}
+
+impl EarlyAssignment for ExtrinsicValueAssignment {
+    fn should_assign(&self, quote: &Quote, dividend: Option<Money>) -> bool {
+        let extrinsic = quote.extrinsic_value();
+
+        if extrinsic < self.threshold {
+            return true;
+        }
+
+        match dividend {
+            Some(d) => quote.is_call() && d > extrinsic,
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::quote_builder::QuoteBuilder;
+
+    fn itm_call(bid: Money, ask: Money) -> Quote {
+        QuoteBuilder::new()
+            .with_bid_ask(bid, ask)
+            .with_underlying(Money::new(110, 0))
+            .build()
+    }
+
+    #[test]
+    fn test_should_assign() {
+        let model = ExtrinsicValueAssignment::new(Money::new(0, 10));
+
+        // $0.50 of extrinsic value left
+        let call = itm_call(Money::new(10, 40), Money::new(10, 60));
+
+        assert!(!model.should_assign(&call, None));
+        assert!(!model.should_assign(&call, Some(Money::new(0, 40))));
+        assert!(model.should_assign(&call, Some(Money::new(0, 60))));
+
+        // $0.05 of extrinsic value left
+        let call = itm_call(Money::new(10, 0), Money::new(10, 10));
+        assert!(model.should_assign(&call, None));
+    }
+}
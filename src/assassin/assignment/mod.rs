@@ -0,0 +1 @@
+pub mod extrinsic_value;
@@ -0,0 +1,231 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+
+use assassin::benchmark::BenchmarkStats;
+use assassin::broker::Broker;
+use assassin::equity_point::EquityPoint;
+use assassin::filled_order::FilledOrder;
+use assassin::journal_entry::JournalEntry;
+use assassin::json::Json;
+use assassin::margin_usage::MarginUsage;
+use assassin::position::Position;
+use assassin::returns_table::ReturnsTable;
+use assassin::stats::PerformanceStats;
+use assassin::targets::Verdict;
+use assassin::trade::Trade;
+use assassin::trade_stats::TradeStats;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Everything a finished Simulation knows, in one place, for other programs to
+// read instead of scraping the log.
+#[derive(Clone)]
+pub struct BacktestResult {
+    // ===== config =====
+    models: Vec<&'static str>,
+    starting_balance: Money,
+    risk_free_rate: f64,
+    margin_calculator: &'static str,
+    mark_method: &'static str,
+
+    // ===== summary =====
+    ending_balance: Money,
+    commission: Money,
+    // see FilledOrder::slippage()
+    slippage: Money,
+    interest: Money,
+    dividends: Money,
+    margin: Vec<MarginUsage>,
+    performance: PerformanceStats,
+    benchmark: Option<BenchmarkStats>,
+    returns: ReturnsTable,
+    trade_stats: TradeStats,
+    verdict: Verdict,
+
+    // ===== details =====
+    equity_curve: Vec<EquityPoint>,
+    trades: Vec<Trade>,
+    positions: Vec<Position>,
+    orders: Vec<Rc<FilledOrder>>,
+    journal: Vec<JournalEntry>,
+}
+
+impl BacktestResult {
+    pub fn new(
+        broker: &Broker,
+        models: Vec<&'static str>,
+        risk_free_rate: f64,
+        performance: PerformanceStats,
+        benchmark: Option<BenchmarkStats>,
+        verdict: Verdict,
+    ) -> BacktestResult {
+        let trades = broker.trades();
+
+        BacktestResult {
+            models: models,
+            starting_balance: broker.opening_balance(),
+            risk_free_rate: risk_free_rate,
+            margin_calculator: broker.margin_calculator_name(),
+            mark_method: broker.mark_method().name(),
+
+            ending_balance: broker.account_balance(),
+            commission: broker.commission_paid(),
+            slippage: broker.filled_orders().iter().map(|o| o.slippage()).sum(),
+            interest: broker.interest_earned() - broker.interest_paid(),
+            dividends: broker.dividends_received(),
+            margin: broker.margin_usage().into_iter().cloned().collect(),
+            performance: performance,
+            benchmark: benchmark,
+            returns: ReturnsTable::new(broker.opening_balance(), broker.equity_curve()),
+            trade_stats: TradeStats::new(&trades),
+            verdict: verdict,
+
+            equity_curve: broker.equity_curve().clone(),
+            trades: trades,
+            positions: broker.positions().into_iter().cloned().collect(),
+            orders: broker.filled_orders().clone(),
+            journal: broker.journal().clone(),
+        }
+    }
+
+    pub fn models(&self) -> &Vec<&'static str> {
+        &self.models
+    }
+
+    pub fn starting_balance(&self) -> Money {
+        self.starting_balance
+    }
+
+    pub fn risk_free_rate(&self) -> f64 {
+        self.risk_free_rate
+    }
+
+    pub fn margin_calculator(&self) -> &'static str {
+        self.margin_calculator
+    }
+
+    pub fn mark_method(&self) -> &'static str {
+        self.mark_method
+    }
+
+    pub fn ending_balance(&self) -> Money {
+        self.ending_balance
+    }
+
+    pub fn profit(&self) -> Money {
+        self.ending_balance - self.starting_balance
+    }
+
+    pub fn commission(&self) -> Money {
+        self.commission
+    }
+
+    pub fn slippage(&self) -> Money {
+        self.slippage
+    }
+
+    pub fn interest(&self) -> Money {
+        self.interest
+    }
+
+    pub fn dividends(&self) -> Money {
+        self.dividends
+    }
+
+    // see Broker::margin_usage()
+    pub fn margin(&self) -> &Vec<MarginUsage> {
+        &self.margin
+    }
+
+    pub fn performance(&self) -> &PerformanceStats {
+        &self.performance
+    }
+
+    pub fn benchmark(&self) -> Option<&BenchmarkStats> {
+        self.benchmark.as_ref()
+    }
+
+    pub fn returns(&self) -> &ReturnsTable {
+        &self.returns
+    }
+
+    pub fn trade_stats(&self) -> &TradeStats {
+        &self.trade_stats
+    }
+
+    pub fn verdict(&self) -> &Verdict {
+        &self.verdict
+    }
+
+    pub fn equity_curve(&self) -> &Vec<EquityPoint> {
+        &self.equity_curve
+    }
+
+    pub fn trades(&self) -> &Vec<Trade> {
+        &self.trades
+    }
+
+    pub fn positions(&self) -> &Vec<Position> {
+        &self.positions
+    }
+
+    #[allow(dead_code)]
+    pub fn orders(&self) -> &Vec<Rc<FilledOrder>> {
+        &self.orders
+    }
+
+    pub fn to_json(&self) -> Json {
+        let first_date = self.equity_curve.first().map(|p| p.date());
+        let last_date = self.equity_curve.last().map(|p| p.date());
+
+        Json::Object(vec![
+            (
+                "config",
+                Json::Object(vec![
+                    ("models", Json::Array(self.models.iter().map(|m| Json::string(m)).collect())),
+                    ("starting_balance", Json::Money(self.starting_balance)),
+                    ("risk_free_rate", Json::Number(self.risk_free_rate)),
+                    ("margin_calculator", Json::string(self.margin_calculator)),
+                    ("mark_method", Json::string(self.mark_method)),
+                    ("first_date", Json::optional(first_date, Json::date)),
+                    ("last_date", Json::optional(last_date, Json::date)),
+                ]),
+            ),
+            (
+                "summary",
+                Json::Object(vec![
+                    ("ending_balance", Json::Money(self.ending_balance)),
+                    ("profit", Json::Money(self.profit())),
+                    ("commission", Json::Money(self.commission)),
+                    ("slippage", Json::Money(self.slippage)),
+                    ("interest", Json::Money(self.interest)),
+                    ("dividends", Json::Money(self.dividends)),
+                    ("margin", array(&self.margin, |m| m.to_json(self.profit()))),
+                    ("performance", self.performance.to_json()),
+                    ("benchmark", Json::optional(self.benchmark.as_ref(), |b| b.to_json())),
+                    ("returns", self.returns.to_json()),
+                    ("trades", self.trade_stats.to_json()),
+                    ("verdict", self.verdict.to_json()),
+                ]),
+            ),
+            ("equity_curve", array(&self.equity_curve, EquityPoint::to_json)),
+            ("trades", array(&self.trades, Trade::to_json)),
+            ("positions", array(&self.positions, Position::to_json)),
+            ("orders", array(&self.orders, |o| o.to_json())),
+            ("journal", array(&self.journal, JournalEntry::to_json)),
+        ])
+    }
+
+    pub fn write_json(&self, filename: &str) -> io::Result<()> {
+        let mut file = File::create(filename)?;
+
+        writeln!(file, "{}", self.to_json())
+    }
+}
+
+fn array<T, F: Fn(&T) -> Json>(values: &[T], to_json: F) -> Json {
+    Json::Array(values.iter().map(to_json).collect())
+}
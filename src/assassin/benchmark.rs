@@ -0,0 +1,343 @@
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+use assassin::broker::Broker;
+use assassin::csv;
+use assassin::equity_point::EquityPoint;
+use assassin::json::Json;
+use assassin::stats::{self, PerformanceStats, TRADING_DAYS_PER_YEAR};
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// What a run is compared to: the daily closes of something that could have
+// been bought and held instead.  Days missing from the series use the most
+// recent close before them.
+#[derive(Clone)]
+pub struct Benchmark {
+    name: String,
+    // sorted by date
+    closes: Vec<(i32, f64)>,
+}
+
+impl Benchmark {
+    pub fn new(name: &str, closes: Vec<(DateTime<Utc>, f64)>) -> Benchmark {
+        if closes.is_empty() {
+            panic!("no closes found");
+        }
+
+        let mut closes: Vec<(i32, f64)> = closes
+            .into_iter()
+            .map(|(date, close)| (date.num_days_from_ce(), close))
+            .collect();
+        closes.sort_by_key(|c| c.0);
+
+        Benchmark {
+            name: name.to_string(),
+            closes: closes,
+        }
+    }
+
+    // buying and holding the underlying, using the closes the broker recorded
+    pub fn buy_and_hold(broker: &Broker, symbol: &str) -> Option<Benchmark> {
+        let closes = broker.underlying_closes(symbol)?;
+
+        if closes.is_empty() {
+            return None;
+        }
+
+        Some(Benchmark::new(
+            symbol,
+            closes.iter().map(|&(date, close)| (date, stats::dollars(close))).collect(),
+        ))
+    }
+
+    // an index (or anything else) from a CSV file, one "YYYY-MM-DD,close" per
+    // line
+    pub fn from_csv(name: &str, filename: &str) -> Result<Benchmark, String> {
+        let file = File::open(filename).map_err(|e| format!("{}: {}", filename, e))?;
+
+        let lines: Vec<String> = BufReader::new(file)
+            .lines()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("{}: {}", filename, e))?;
+
+        Benchmark::from_lines(name, lines.iter().map(|l| l.as_str()))
+            .map_err(|e| format!("{}: {}", filename, e))
+    }
+
+    pub fn from_lines<'a, I>(name: &str, lines: I) -> Result<Benchmark, String>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut closes = vec![];
+
+        for (number, v) in csv::rows(lines, 0) {
+            if v.len() != 2 {
+                return Err(format!("line {}: expected date,close", number));
+            }
+
+            let date = csv::parse_date(v[0]).map_err(|e| format!("line {}: {}", number, e))?;
+            let close: f64 = v[1]
+                .parse()
+                .map_err(|_| format!("line {}: '{}' isn't a close", number, v[1]))?;
+
+            closes.push((date, close));
+        }
+
+        if closes.is_empty() {
+            return Err("no closes found".to_string());
+        }
+
+        Ok(Benchmark::new(name, closes))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn close_on(&self, date: DateTime<Utc>) -> f64 {
+        let day = date.num_days_from_ce();
+
+        // before the series starts we use the first close we have
+        match self.closes.iter().rev().find(|&&(d, _)| d <= day) {
+            Some(&(_, close)) => close,
+            None => self.closes[0].1,
+        }
+    }
+
+    // the opening balance put into the benchmark at the close of the first
+    // day of the run, valued at the close of every day of it
+    pub fn equity_curve(&self, opening_balance: Money, run: &[EquityPoint]) -> Vec<EquityPoint> {
+        let first = match run.first() {
+            Some(p) => self.close_on(p.date()),
+            None => return vec![],
+        };
+
+        run.iter()
+            .map(|p| {
+                let growth = stats::ratio(self.close_on(p.date()), first);
+                let cents = (opening_balance.raw_value() as f64 * growth).round() as i32;
+
+                EquityPoint::new(
+                    p.date(),
+                    Money::zero(),
+                    Money::from_cents(cents),
+                    Money::zero(),
+                    1,
+                )
+            })
+            .collect()
+    }
+}
+
+// How a run did next to its Benchmark, from their daily returns.  Alpha,
+// tracking error and the information ratio are annualized.
+#[derive(Clone)]
+pub struct BenchmarkStats {
+    name: String,
+    // of the benchmark itself
+    performance: PerformanceStats,
+    beta: f64,
+    alpha: f64,
+    correlation: f64,
+    tracking_error: f64,
+    information_ratio: f64,
+    // the run's average return over the benchmark's, on days the benchmark
+    // was up (or down).  1.0 is 100%.
+    up_capture: f64,
+    down_capture: f64,
+}
+
+impl BenchmarkStats {
+    // risk_free_rate is annualized (0.02 is 2%)
+    pub fn new(
+        benchmark: &Benchmark,
+        opening_balance: Money,
+        equity_curve: &[EquityPoint],
+        risk_free_rate: f64,
+    ) -> BenchmarkStats {
+        let benchmark_curve = benchmark.equity_curve(opening_balance, equity_curve);
+
+        let returns = stats::daily_returns(opening_balance, equity_curve);
+        let benchmark_returns = stats::daily_returns(opening_balance, &benchmark_curve);
+
+        let beta = stats::ratio(
+            covariance(&returns, &benchmark_returns),
+            stats::standard_deviation(&benchmark_returns).powi(2),
+        );
+
+        let daily_risk_free = risk_free_rate / TRADING_DAYS_PER_YEAR;
+        let alpha = (stats::mean(&returns) - daily_risk_free)
+            - beta * (stats::mean(&benchmark_returns) - daily_risk_free);
+
+        let active: Vec<f64> = returns
+            .iter()
+            .zip(benchmark_returns.iter())
+            .map(|(r, b)| r - b)
+            .collect();
+        let tracking_error = stats::standard_deviation(&active) * TRADING_DAYS_PER_YEAR.sqrt();
+
+        BenchmarkStats {
+            name: benchmark.name().to_string(),
+            performance: PerformanceStats::new(opening_balance, &benchmark_curve, risk_free_rate),
+            beta: beta,
+            alpha: alpha * TRADING_DAYS_PER_YEAR,
+            correlation: stats::ratio(
+                covariance(&returns, &benchmark_returns),
+                stats::standard_deviation(&returns) * stats::standard_deviation(&benchmark_returns),
+            ),
+            tracking_error: tracking_error,
+            information_ratio: stats::ratio(
+                stats::mean(&active) * TRADING_DAYS_PER_YEAR,
+                tracking_error,
+            ),
+            up_capture: capture(&returns, &benchmark_returns, |b| b > 0.0),
+            down_capture: capture(&returns, &benchmark_returns, |b| b < 0.0),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn performance(&self) -> &PerformanceStats {
+        &self.performance
+    }
+
+    pub fn beta(&self) -> f64 {
+        self.beta
+    }
+
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn correlation(&self) -> f64 {
+        self.correlation
+    }
+
+    pub fn tracking_error(&self) -> f64 {
+        self.tracking_error
+    }
+
+    pub fn information_ratio(&self) -> f64 {
+        self.information_ratio
+    }
+
+    pub fn up_capture(&self) -> f64 {
+        self.up_capture
+    }
+
+    pub fn down_capture(&self) -> f64 {
+        self.down_capture
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("name", Json::string(&self.name)),
+            ("performance", self.performance.to_json()),
+            ("beta", Json::Number(self.beta)),
+            ("alpha", Json::Number(self.alpha)),
+            ("correlation", Json::Number(self.correlation)),
+            ("tracking_error", Json::Number(self.tracking_error)),
+            ("information_ratio", Json::Number(self.information_ratio)),
+            ("up_capture", Json::Number(self.up_capture)),
+            ("down_capture", Json::Number(self.down_capture)),
+        ])
+    }
+}
+
+// of a sample
+fn covariance(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() < 2 {
+        return 0.0;
+    }
+
+    let mean_a = stats::mean(a);
+    let mean_b = stats::mean(b);
+
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x - mean_a) * (y - mean_b))
+        .sum::<f64>() / (a.len() - 1) as f64
+}
+
+fn capture<F: Fn(f64) -> bool>(returns: &[f64], benchmark_returns: &[f64], days: F) -> f64 {
+    let (run, benchmark): (Vec<f64>, Vec<f64>) = returns
+        .iter()
+        .zip(benchmark_returns.iter())
+        .filter(|&(_, b)| days(*b))
+        .unzip();
+
+    stats::ratio(stats::mean(&run), stats::mean(&benchmark))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::equity_curve_builder::EquityCurveBuilder;
+
+    #[test]
+    fn test_equity_curve() {
+        // nothing on the 3rd, so the 2nd's close is used
+        let lines = vec!["date,close", "2013-01-02,50.0", "2013-01-04,55.0"];
+        let benchmark = Benchmark::from_lines("index", lines.into_iter()).unwrap();
+
+        let run = EquityCurveBuilder::new(&[100, 100, 100]).build();
+        let equity = benchmark.equity_curve(Money::new(100, 0), &run);
+
+        assert!(equity[1].equity() == Money::new(100, 0));
+        assert!(equity[2].equity() == Money::new(110, 0));
+    }
+
+    #[test]
+    fn test_malformed_rows() {
+        let error = |lines: Vec<&'static str>| match Benchmark::from_lines("", lines.into_iter()) {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => e,
+        };
+
+        assert_eq!(error(vec!["date,close", "2013-01-02,x"]), "line 2: 'x' isn't a close");
+        assert_eq!(error(vec!["2013-01-02"]), "line 1: expected date,close");
+        assert_eq!(error(vec!["2013-01-32,50.0"]), "line 1: '2013-01-32' isn't a date");
+        assert_eq!(error(vec!["date,close", ""]), "no closes found");
+    }
+
+    #[test]
+    fn test_twice_the_benchmark() {
+        let benchmark = Benchmark::new(
+            "index",
+            vec![
+                (Utc.ymd(2013, 1, 2).and_hms(0, 0, 0), 100.0),
+                (Utc.ymd(2013, 1, 3).and_hms(0, 0, 0), 101.0),
+                (Utc.ymd(2013, 1, 4).and_hms(0, 0, 0), 99.99),
+                (Utc.ymd(2013, 1, 5).and_hms(0, 0, 0), 101.9898),
+            ],
+        );
+
+        // twice whatever it does
+        let mut run = EquityCurveBuilder::new(&[100_000, 102_000, 99_960]).build();
+        run.push(EquityPoint::new(
+            Utc.ymd(2013, 1, 5).and_hms(0, 0, 0),
+            Money::new(103_958, 40),
+            Money::zero(),
+            Money::zero(),
+            1,
+        ));
+
+        let stats = BenchmarkStats::new(&benchmark, Money::new(100_000, 0), &run, 0.0);
+
+        assert!((stats.beta() - 2.0).abs() < 0.01);
+        assert!((stats.correlation() - 1.0).abs() < 0.001);
+        assert!((stats.up_capture() - 2.0).abs() < 0.01);
+        assert!((stats.down_capture() - 2.0).abs() < 0.01);
+        assert!(stats.alpha().abs() < 0.01);
+    }
+}
@@ -0,0 +1,241 @@
+use std::mem;
+use std::rc::Rc;
+
+use assassin::broker::Broker;
+use assassin::corporate_action::{CorporateAction, CorporateActionKind};
+use assassin::journal_entry::JournalEntryKind;
+use assassin::position::{Position, PositionKey};
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+impl Broker {
+    #[allow(dead_code)]
+    pub fn add_corporate_action(&mut self, action: CorporateAction) {
+        self.corporate_actions.push(action);
+        self.corporate_actions.sort_by_key(|a| a.ex_date());
+    }
+
+    #[allow(dead_code)]
+    pub fn add_corporate_actions(&mut self, actions: Vec<CorporateAction>) {
+        self.corporate_actions.extend(actions);
+        self.corporate_actions.sort_by_key(|a| a.ex_date());
+    }
+
+    // the dividends (if any) going ex after today and on or before `date`
+    pub(super) fn dividend_before(&self, symbol: &str, date: DateTime<Utc>) -> Option<Money> {
+        let dividends: Vec<Money> = self.corporate_actions
+            .iter()
+            .filter(|a| &*a.symbol() == symbol && a.is_dividend())
+            .filter(|a| a.goes_ex_between(self.current_date, date))
+            .map(|a| a.amount())
+            .collect();
+
+        if dividends.is_empty() {
+            None
+        } else {
+            Some(dividends.into_iter().sum())
+        }
+    }
+
+    // applies everything going ex on or before `date`, whose quotes are about
+    // to come in, so that positions line up with them.  this happens after
+    // expirations so that options expiring the day before settle on their
+    // original terms.
+    pub(super) fn process_corporate_actions(&mut self, date: DateTime<Utc>) {
+        let until = date.num_days_from_ce();
+
+        let (due, pending): (Vec<CorporateAction>, Vec<CorporateAction>) =
+            mem::take(&mut self.corporate_actions)
+                .into_iter()
+                .partition(|a| a.ex_date().num_days_from_ce() <= until);
+
+        self.corporate_actions = pending;
+
+        for action in due {
+            info!("** CORPORATE ACTION: {}", action.summary());
+
+            let symbol = action.symbol();
+
+            match action.kind() {
+                CorporateActionKind::CashDividend => {
+                    self.pay_dividend(&symbol, action.amount());
+                }
+                CorporateActionKind::SpecialDividend => {
+                    let amount = action.amount();
+
+                    self.pay_dividend(&symbol, amount);
+                    self.adjust_option_positions(&symbol, |p| p.reduce_strike_price(amount));
+                }
+                CorporateActionKind::Split { to, from } => {
+                    self.split_stock_position(&symbol, to, from);
+                    self.adjust_option_positions(&symbol, |p| p.apply_split(to, from));
+                }
+            }
+
+            self.applied_corporate_actions.push(action);
+        }
+    }
+
+    // credits (or, for short shares, debits) a per-share dividend.  the
+    // underlying is marked down by the same amount until its quotes for the
+    // ex-date come in.
+    fn pay_dividend(&mut self, symbol: &Rc<str>, amount: Money) {
+        if let Some(price) = self.underlying_prices.get_mut(symbol) {
+            *price -= amount;
+        }
+
+        let shares = match self.stock_positions.get(symbol) {
+            Some(s) => s.quantity(),
+            None => 0,
+        };
+
+        if shares == 0 {
+            return;
+        }
+
+        let cash = amount * shares;
+
+        info!("   {} shares of {} paid a {} dividend: {}", shares, symbol, amount, cash);
+
+        let reference = format!("{} shares of {} @ {}", shares, symbol, amount);
+        self.post(JournalEntryKind::Dividend, cash, Rc::from(reference.as_str()));
+        self.dividends_received += cash;
+    }
+
+    fn split_stock_position(&mut self, symbol: &Rc<str>, to: i32, from: i32) {
+        let price = match self.underlying_prices.get(symbol) {
+            Some(p) => *p,
+            None => return,
+        };
+
+        self.underlying_prices
+            .insert(Rc::clone(symbol), price * from / to);
+
+        let cash_in_lieu = match self.stock_positions.get_mut(symbol) {
+            Some(s) => s.apply_split(to, from, price),
+            None => return,
+        };
+
+        self.post(JournalEntryKind::CashInLieu, cash_in_lieu, Rc::clone(symbol));
+    }
+
+    // adjusts the terms of the open option positions on `symbol` and files
+    // them under their new names so that quotes for the adjusted series (and
+    // orders from models) find them
+    fn adjust_option_positions<F>(&mut self, symbol: &Rc<str>, adjust: F)
+    where
+        F: Fn(&mut Position),
+    {
+        let keys: Vec<PositionKey> = self.open_positions()
+            .iter()
+            .filter(|p| p.symbol() == *symbol)
+            .map(|p| p.key())
+            .collect();
+
+        let mut adjusted = vec![];
+
+        for key in keys {
+            let mut position = self.positions.remove(&key).unwrap();
+            adjust(&mut position);
+
+            info!(
+                "   {} is now {} x {} ({} shares per contract)",
+                key.1,
+                position.quantity(),
+                position.name(),
+                position.multiplier(),
+            );
+
+            adjusted.push(position);
+        }
+
+        for position in adjusted {
+            // a position that was closed before the event and happens to have
+            // the new name keeps its history under another key
+            if let Some(old) = self.positions.remove(&position.key()) {
+                let name = format!("{} (before {})", old.name(), self.current_date.format("%F"));
+                self.positions.insert((old.group(), Rc::from(name.as_str())), old);
+            }
+
+            self.positions.insert(position.key(), position);
+        }
+    }
+
+    pub fn applied_corporate_actions(&self) -> &Vec<CorporateAction> {
+        &self.applied_corporate_actions
+    }
+
+    // net of dividends owed on short shares
+    pub fn dividends_received(&self) -> Money {
+        self.dividends_received
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::broker::tests::broker_with;
+    use assassin::order::Order;
+    use assassin::quote_builder::QuoteBuilder;
+
+    #[test]
+    fn test_split_adjusts_option_positions() {
+        let quote = |day, strike, bid, underlying| {
+            QuoteBuilder::new()
+                .expiring(2014, 7, 19)
+                .quoted_on(2014, 6, day)
+                .with_strike(strike)
+                .with_bid_ask(bid, bid + Money::new(0, 7))
+                .with_underlying(underlying)
+                .build()
+        };
+
+        let mut quotes = vec![];
+
+        for day in 5..7 {
+            quotes.push(quote(day, 560, Money::new(90, 0), Money::new(644, 0)));
+            quotes.push(quote(day, 700, Money::new(5, 0), Money::new(644, 0)));
+        }
+
+        for day in 9..11 {
+            quotes.push(quote(day, 80, Money::new(13, 0), Money::new(93, 0)));
+            quotes.push(quote(day, 100, Money::new(0, 75), Money::new(93, 0)));
+        }
+
+        let mut broker = broker_with(quotes);
+        broker.add_corporate_action(CorporateAction::split(
+            "AAPL",
+            Utc.ymd(2014, 6, 9).and_hms(0, 0, 0),
+            7,
+            1,
+        ));
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+
+        // the model still gets the last pre-split day's chain as it was
+        assert!(broker.process_simulation_data());
+        assert_eq!(broker.call_quotes_for("AAPL").len(), 2);
+        assert!(broker.underlying_price_for("AAPL") == Money::new(644, 0));
+        assert_eq!(broker.open_positions()[0].quantity(), 1);
+
+        // applied before the first post-split session
+        assert!(broker.process_simulation_data());
+
+        assert_eq!(broker.applied_corporate_actions().len(), 1);
+
+        let position = broker.open_positions()[0].clone();
+        assert_eq!(position.quantity(), 7);
+        assert!(position.strike_price() == Money::new(80, 0));
+
+        // the position is marked with the post-split chain's quotes
+        let quote = broker.quote_for(position.name()).unwrap();
+        assert!(position.current_value(&quote) == Money::new(9_100, 0));
+    }
+}
@@ -0,0 +1,111 @@
+use std::fs::{self, File};
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+use assassin::broker::Broker;
+use assassin::equity_point::EquityPoint;
+use assassin::filled_order::FilledOrder;
+use assassin::greeks::Greeks;
+use assassin::position::Position;
+use assassin::trade::Trade;
+
+impl Broker {
+    // fills.csv, positions.csv, trades.csv, equity.csv and greeks.csv, one
+    // row per fill, position, round trip or trading day
+    pub fn write_csv(&self, directory: &str) -> io::Result<()> {
+        let directory = Path::new(directory);
+        fs::create_dir_all(directory)?;
+
+        let mut file = File::create(directory.join("fills.csv"))?;
+        writeln!(file, "{}", FilledOrder::csv_header())?;
+
+        for o in self.filled_orders.iter() {
+            writeln!(file, "{}", o.to_csv())?;
+        }
+
+        let mut file = File::create(directory.join("positions.csv"))?;
+        writeln!(file, "{}", Position::csv_header())?;
+
+        for p in self.positions() {
+            writeln!(file, "{}", p.to_csv())?;
+        }
+
+        let mut file = File::create(directory.join("trades.csv"))?;
+        writeln!(file, "{}", Trade::csv_header())?;
+
+        for t in self.trades() {
+            writeln!(file, "{}", t.to_csv())?;
+        }
+
+        let mut file = File::create(directory.join("equity.csv"))?;
+        writeln!(file, "{}", EquityPoint::csv_header())?;
+
+        for p in self.equity_curve.iter() {
+            writeln!(file, "{}", p.to_csv())?;
+        }
+
+        let mut file = File::create(directory.join("greeks.csv"))?;
+        writeln!(file, "date,{}", Greeks::csv_header())?;
+
+        for &(date, ref greeks) in self.greeks_history.iter() {
+            writeln!(file, "{},{}", date.format("%Y-%m-%d"), greeks.to_csv())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use std::env;
+    use std::io::Read;
+
+    use assassin::broker::tests::{broker_with, day_of_quotes};
+    use assassin::order::Order;
+
+    #[test]
+    fn test_write_csv() {
+        let mut quotes = day_of_quotes(2);
+        quotes.extend(day_of_quotes(3));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+        assert!(broker.process_simulation_data());
+        assert!(!broker.process_simulation_data());
+
+        let directory = env::temp_dir().join("assassin_test_write_csv");
+        broker.write_csv(directory.to_str().unwrap()).unwrap();
+
+        let read = |name: &str| {
+            let mut contents = String::new();
+            File::open(directory.join(name))
+                .unwrap()
+                .read_to_string(&mut contents)
+                .unwrap();
+            contents
+        };
+
+        // bought, then sold by the broker at the end
+        let fills = read("fills.csv");
+        assert_eq!(fills.lines().count(), 3);
+        assert!(fills.lines().nth(1).unwrap().starts_with("2013-01-02,"));
+
+        assert_eq!(read("positions.csv").lines().count(), 2);
+        assert_eq!(read("trades.csv").lines().count(), 2);
+        assert!(read("trades.csv").lines().nth(1).unwrap().ends_with(",true"));
+        assert_eq!(read("equity.csv").lines().count(), 4);
+        assert_eq!(
+            read("greeks.csv").lines().next(),
+            Some("date,delta,dollar_delta,gamma,theta,vega,beta_weighted_delta")
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+
+use assassin::broker::Broker;
+use assassin::journal_entry::{JournalEntry, JournalEntryKind};
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+impl Broker {
+    // every change to the cash balance goes through here
+    pub(super) fn post(&mut self, kind: JournalEntryKind, amount: Money, reference: Rc<str>) {
+        if amount == Money::zero() {
+            return;
+        }
+
+        self.balance += amount;
+
+        let entry = JournalEntry::new(
+            self.journal.len() as u32 + 1,
+            self.current_date,
+            kind,
+            amount,
+            self.balance,
+            reference,
+        );
+
+        self.journal.push(entry);
+    }
+
+    #[allow(dead_code)]
+    pub fn opening_balance(&self) -> Money {
+        self.opening_balance
+    }
+
+    pub fn journal(&self) -> &Vec<JournalEntry> {
+        &self.journal
+    }
+
+    #[allow(dead_code)]
+    pub fn journal_entries_of(&self, kind: JournalEntryKind) -> Vec<&JournalEntry> {
+        self.journal.iter().filter(|e| e.kind() == kind).collect()
+    }
+
+    // entries posted on or between the two dates
+    #[allow(dead_code)]
+    pub fn journal_entries_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<&JournalEntry> {
+        let (start, end) = (start.num_days_from_ce(), end.num_days_from_ce());
+
+        self.journal
+            .iter()
+            .filter(|e| {
+                let d = e.date().num_days_from_ce();
+                d >= start && d <= end
+            })
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn journal_total(&self, kind: JournalEntryKind) -> Money {
+        self.journal
+            .iter()
+            .filter(|e| e.kind() == kind)
+            .map(|e| e.amount())
+            .sum()
+    }
+
+    // replays the journal from the opening balance and checks every running
+    // balance along the way, then checks where it ends up against the cash
+    // the fills, stock positions, interest accruals and dividends account for
+    pub fn reconcile_journal(&self) -> Result<(), String> {
+        let mut balance = self.opening_balance;
+
+        for e in self.journal.iter() {
+            balance += e.amount();
+
+            if balance != e.balance() {
+                return Err(format!(
+                    "entry #{} should leave {} but recorded {}",
+                    e.id(),
+                    balance,
+                    e.balance(),
+                ));
+            }
+        }
+
+        let expected = self.balance_from_records();
+
+        if balance != expected {
+            return Err(format!(
+                "journal adds up to {} but the records add up to {}",
+                balance, expected,
+            ));
+        }
+
+        Ok(())
+    }
+
+    // the cash balance rebuilt without the journal
+    fn balance_from_records(&self) -> Money {
+        let fills: Money = self.filled_orders
+            .iter()
+            .map(|o| o.canonical_cost_basis() - o.commission())
+            .sum();
+
+        // what was paid for the shares still held isn't in the realized
+        // profit, and cash in lieu from splits is
+        let stock: Money = self.stock_positions
+            .values()
+            .map(|s| s.realized_profit() - s.cost_basis())
+            .sum();
+
+        let interest: Money = self.interest_accruals.iter().map(|a| a.amount()).sum();
+
+        self.opening_balance + fills + stock + interest + self.dividends_received
+    }
+
+    #[allow(dead_code)]
+    pub fn write_journal(&self, filename: &str) -> io::Result<()> {
+        let mut file = File::create(filename)?;
+
+        writeln!(file, "{}", JournalEntry::csv_header())?;
+
+        for e in self.journal.iter() {
+            writeln!(file, "{}", e.to_csv())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::broker::tests::broker;
+    use assassin::order::Order;
+
+    #[test]
+    fn test_journal_records_every_balance_change() {
+        let mut broker = broker();
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+
+        broker.process_order(Order::new_buy_open_order(&quote, 2, quote.ask()));
+        broker.process_order(Order::new_sell_close_order(&quote, 1, quote.bid()));
+
+        let kinds: Vec<JournalEntryKind> = broker.journal().iter().map(|e| e.kind()).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                JournalEntryKind::Premium,
+                JournalEntryKind::Commission,
+                JournalEntryKind::Premium,
+                JournalEntryKind::Commission,
+            ]
+        );
+
+        let commish = broker.journal_total(JournalEntryKind::Commission);
+        assert!(commish == Money::zero() - broker.commission_paid());
+
+        // bought 2 and sold 1 at the $7.10 midpoint
+        assert!(broker.journal_total(JournalEntryKind::Premium) == Money::new(-710, 0));
+        assert!(broker.journal()[3].balance() == broker.account_balance());
+        assert!(broker.reconcile_journal().is_ok());
+    }
+}
@@ -0,0 +1,2850 @@
+use std::mem;
+use std::rc::Rc;
+
+use assassin::circuit_breaker::{BreakerAction, CircuitBreaker, CircuitBreakerEvent};
+use assassin::combo_order::ComboOrder;
+use assassin::conditional_order::ConditionalOrder;
+use assassin::corporate_action::CorporateAction;
+use assassin::equity_point::EquityPoint;
+use assassin::exercise::{ExerciseEvent, ExerciseKind};
+use assassin::filled_order::FilledOrder;
+use assassin::greeks::Greeks;
+use assassin::holding::Holding;
+use assassin::interest::accrual::InterestAccrual;
+use assassin::journal_entry::{JournalEntry, JournalEntryKind};
+use assassin::lot::{ClosedLot, HoldingPeriod, LotMatching};
+use assassin::margin::reg_t::RegT;
+use assassin::margin_requirement::{MarginCall, MarginRequirement};
+use assassin::margin_usage::MarginUsage;
+use assassin::mark_method::MarkMethod;
+use assassin::order::Order;
+use assassin::order_request::OrderRequest;
+use assassin::pnl_snapshot::PnlSnapshot;
+use assassin::position::{Position, PositionKey};
+use assassin::quote::Quote;
+use assassin::risk_limits::{self, RiskLimits};
+use assassin::stock_position::StockPosition;
+use assassin::submitted_order::{OrderStatus, SubmittedOrder};
+use assassin::trade::Trade;
+use assassin::trade_group::TradeGroup;
+use assassin::traits::*;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate fnv;
+use self::fnv::FnvHashMap;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+mod corporate_actions;
+mod export;
+mod journal;
+
+// a margin calculator other than the one being enforced, to compare the
+// leverage of different regimes
+struct MarginComparison {
+    calculator: Box<MarginCalculator>,
+    usage: MarginUsage,
+}
+
+// a ConditionalOrder waiting for its trigger
+struct WorkingOrder {
+    // of the SubmittedOrder
+    id: u32,
+    oco_group: Option<u32>,
+    order: ConditionalOrder,
+}
+
+pub struct Broker {
+    balance: Money,
+    opening_balance: Money,
+    journal: Vec<JournalEntry>,
+    positions: FnvHashMap<PositionKey, Position>,
+    lot_matching: LotMatching,
+    mark_method: MarkMethod,
+    // one snapshot per trading day
+    account_pnl: Vec<PnlSnapshot>,
+    // one point per trading day
+    equity_curve: Vec<EquityPoint>,
+    // the underlying price of every symbol seen, on the same days
+    underlying_closes: FnvHashMap<Rc<str>, Vec<(DateTime<Utc>, Money)>>,
+    // one snapshot for every day each position was open
+    position_pnl: FnvHashMap<PositionKey, Vec<PnlSnapshot>>,
+    // the portfolio's exposures at the close of each trading day
+    greeks_history: Vec<(DateTime<Utc>, Greeks)>,
+    // used for theta
+    risk_free_rate: f64,
+    // to the market, for beta weighting delta.  symbols without one are 1.0.
+    betas: FnvHashMap<Rc<str>, f64>,
+    filled_orders: Vec<Rc<FilledOrder>>,
+    submitted_orders: Vec<SubmittedOrder>,
+    commission_schedule: Box<Commission>,
+    commission_paid: Money,
+    data_feed: Box<DataFeed>,
+    // TODO: convert this into a FnvHashMap<Rc<str>, FnvHashMap<Rc<str>, Quote>>
+    quotes: FnvHashMap<Rc<str>, Quote>,
+    current_date: DateTime<Utc>,
+    quotes_processed: i32,
+    quote_map_capacity: usize,
+    underlying_prices: FnvHashMap<Rc<str>, Money>,
+    combo_count: u32,
+    working_orders: Vec<WorkingOrder>,
+    oco_group_count: u32,
+    stock_positions: FnvHashMap<Rc<str>, StockPosition>,
+    exercise_events: Vec<ExerciseEvent>,
+    cash_settled_symbols: Vec<Rc<str>>,
+    auto_exercise_threshold: Money,
+    early_assignment: Option<Box<EarlyAssignment>>,
+    // sorted by ex-date, the ones that have gone ex are moved to applied_*
+    corporate_actions: Vec<CorporateAction>,
+    applied_corporate_actions: Vec<CorporateAction>,
+    dividends_received: Money,
+    margin_calculator: Box<MarginCalculator>,
+    margin_calls: Vec<MarginCall>,
+    margin_comparisons: Vec<MarginComparison>,
+    credit_interest_rate: Option<Box<InterestRate>>,
+    debit_interest_rate: Option<Box<InterestRate>>,
+    interest_accruals: Vec<InterestAccrual>,
+    risk_limits: RiskLimits,
+    // the ones that have tripped are moved to circuit_breaker_events
+    circuit_breakers: Vec<CircuitBreaker>,
+    circuit_breaker_events: Vec<CircuitBreakerEvent>,
+
+    // statistics for simulation
+    margin_usage: MarginUsage,
+    highest_realized_account_balance: Money,
+    lowest_realized_account_balance: Money,
+    highest_unrealized_account_balance: Money,
+    lowest_unrealized_account_balance: Money,
+    final_unrealized_account_balance: Money,
+
+    // TODO: add vars for realized and unrealized high/low balances
+    carried_over_quote: Option<Quote>,
+}
+
+impl Broker {
+    pub fn new(
+        initial_balance: Money,
+        commission_schedule: Box<Commission>,
+        data_feed: Box<DataFeed>,
+    ) -> Broker {
+        if initial_balance <= Money::zero() {
+            panic!("balance must be > 0.0 (got {})", initial_balance);
+        }
+
+        // this is just so we have a default value
+        let current_date = Utc::now();
+
+        let margin_calculator = RegT::new();
+        let margin_usage = MarginUsage::new(margin_calculator.name(), true);
+
+        Broker {
+            balance: initial_balance,
+            opening_balance: initial_balance,
+            journal: vec![],
+            positions: FnvHashMap::default(),
+            lot_matching: LotMatching::Fifo,
+            mark_method: MarkMethod::BidAsk,
+            account_pnl: vec![],
+            equity_curve: vec![],
+            underlying_closes: FnvHashMap::default(),
+            position_pnl: FnvHashMap::default(),
+            greeks_history: vec![],
+            risk_free_rate: 0.0,
+            betas: FnvHashMap::default(),
+            filled_orders: vec![],
+            submitted_orders: vec![],
+            commission_schedule: commission_schedule,
+            commission_paid: Money::zero(),
+            data_feed: data_feed,
+            quotes: FnvHashMap::with_capacity_and_hasher(0, Default::default()),
+            current_date: current_date,
+            quotes_processed: 0,
+            quote_map_capacity: 0,
+            underlying_prices: FnvHashMap::default(),
+            combo_count: 0,
+            working_orders: vec![],
+            oco_group_count: 0,
+            stock_positions: FnvHashMap::default(),
+            exercise_events: vec![],
+            cash_settled_symbols: ["SPX", "NDX", "RUT", "OEX", "XSP", "DJX", "VIX"]
+                .iter()
+                .map(|s| Rc::from(*s))
+                .collect(),
+            // OCC exercises anything $0.01 or more in the money
+            auto_exercise_threshold: Money::new(0, 1),
+            early_assignment: None,
+            corporate_actions: vec![],
+            applied_corporate_actions: vec![],
+            dividends_received: Money::zero(),
+            margin_calculator: Box::new(margin_calculator),
+            margin_calls: vec![],
+            margin_comparisons: vec![],
+            credit_interest_rate: None,
+            debit_interest_rate: None,
+            interest_accruals: vec![],
+            risk_limits: RiskLimits::new(),
+            circuit_breakers: vec![],
+            circuit_breaker_events: vec![],
+            margin_usage: margin_usage,
+            highest_realized_account_balance: initial_balance,
+            lowest_realized_account_balance: initial_balance,
+            highest_unrealized_account_balance: initial_balance,
+            lowest_unrealized_account_balance: initial_balance,
+            final_unrealized_account_balance: initial_balance,
+            carried_over_quote: None,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn set_risk_free_rate(&mut self, rate: f64) {
+        self.risk_free_rate = rate;
+    }
+
+    #[allow(dead_code)]
+    pub fn set_beta(&mut self, symbol: &str, beta: f64) {
+        self.betas.insert(Rc::from(symbol), beta);
+    }
+
+    #[allow(dead_code)]
+    pub fn set_risk_limits(&mut self, risk_limits: RiskLimits) {
+        self.risk_limits = risk_limits;
+    }
+
+    #[allow(dead_code)]
+    pub fn risk_limits(&self) -> &RiskLimits {
+        &self.risk_limits
+    }
+
+    #[allow(dead_code)]
+    pub fn add_circuit_breaker(&mut self, breaker: CircuitBreaker) {
+        self.circuit_breakers.push(breaker);
+    }
+
+    // options on these symbols settle in cash instead of shares
+    #[allow(dead_code)]
+    pub fn set_cash_settled(&mut self, symbol: &str) {
+        if !self.is_cash_settled(symbol) {
+            self.cash_settled_symbols.push(Rc::from(symbol));
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn set_auto_exercise_threshold(&mut self, threshold: Money) {
+        if threshold < Money::zero() {
+            panic!("threshold must be >= 0.0 (got {})", threshold);
+        }
+
+        self.auto_exercise_threshold = threshold;
+    }
+
+    // how closing fills are matched against the lots of positions opened
+    // after this is called
+    #[allow(dead_code)]
+    pub fn set_lot_matching(&mut self, lot_matching: LotMatching) {
+        self.lot_matching = lot_matching;
+    }
+
+    // how open positions are valued for the account balance and P&L
+    #[allow(dead_code)]
+    pub fn set_mark_method(&mut self, mark_method: MarkMethod) {
+        self.mark_method = mark_method;
+    }
+
+    pub fn mark_method(&self) -> MarkMethod {
+        self.mark_method
+    }
+
+    // short options are only ever assigned at expiration unless this is set
+    #[allow(dead_code)]
+    pub fn set_early_assignment_model(&mut self, model: Box<EarlyAssignment>) {
+        self.early_assignment = Some(model);
+    }
+
+    fn is_cash_settled(&self, symbol: &str) -> bool {
+        self.cash_settled_symbols.iter().any(|s| &**s == symbol)
+    }
+
+    #[allow(dead_code)]
+    pub fn set_margin_calculator(&mut self, calculator: Box<MarginCalculator>) {
+        self.margin_usage = MarginUsage::new(calculator.name(), true);
+        self.margin_calculator = calculator;
+    }
+
+    // paid on a positive cash balance
+    #[allow(dead_code)]
+    pub fn set_credit_interest_rate(&mut self, rate: Box<InterestRate>) {
+        self.credit_interest_rate = Some(rate);
+    }
+
+    // charged on a negative cash balance (i.e., money borrowed on margin)
+    #[allow(dead_code)]
+    pub fn set_debit_interest_rate(&mut self, rate: Box<InterestRate>) {
+        self.debit_interest_rate = Some(rate);
+    }
+
+    // calculated every day alongside the enforced calculator but never enforced
+    pub fn add_comparison_margin_calculator(&mut self, calculator: Box<MarginCalculator>) {
+        self.margin_comparisons.push(MarginComparison {
+            usage: MarginUsage::new(calculator.name(), false),
+            calculator: calculator,
+        });
+    }
+
+    pub fn margin_calculator_name(&self) -> &'static str {
+        self.margin_calculator.name()
+    }
+
+    // the enforced calculator first, then the comparison calculators
+    pub fn margin_usage(&self) -> Vec<&MarginUsage> {
+        let mut usage = vec![&self.margin_usage];
+        usage.extend(self.margin_comparisons.iter().map(|c| &c.usage));
+
+        usage
+    }
+
+    pub fn highest_realized_account_balance(&self) -> Money {
+        self.highest_realized_account_balance
+    }
+
+    pub fn lowest_realized_account_balance(&self) -> Money {
+        self.lowest_realized_account_balance
+    }
+
+    pub fn highest_unrealized_account_balance(&self) -> Money {
+        self.highest_unrealized_account_balance
+    }
+
+    pub fn lowest_unrealized_account_balance(&self) -> Money {
+        self.lowest_unrealized_account_balance
+    }
+
+    pub fn update_statistics(&mut self) {
+        let current_value = self.balance;
+
+        if current_value > self.highest_realized_account_balance {
+            self.highest_realized_account_balance = current_value;
+        }
+
+        if current_value < self.lowest_realized_account_balance {
+            self.lowest_realized_account_balance = current_value;
+        }
+
+        let current_unrealized_value = self.unrealized_account_balance();
+
+        // debug!("current unrealized: {}", current_unrealized_value);
+
+        if current_unrealized_value > self.highest_unrealized_account_balance {
+            self.highest_unrealized_account_balance = current_unrealized_value;
+        }
+
+        if current_unrealized_value < self.lowest_unrealized_account_balance {
+            self.lowest_unrealized_account_balance = current_unrealized_value;
+        }
+    }
+
+    pub fn underlying_price_for(&self, symbol: &str) -> Money {
+        *self.underlying_prices.get(symbol).unwrap()
+    }
+
+    // TODO: this should only return quotes for the desired symbol
+    pub fn quotes_for(&self, _symbol: &str) -> Vec<&Quote> {
+        self.quotes.iter().map(|(_, q)| q).collect()
+    }
+
+    pub fn nearest_quotes_expiring_between_n_days(&self, min: i32, max: i32) -> Vec<&Quote> {
+        if min < 0 {
+            panic!("min must be >= 0 (got: {})", min);
+        }
+
+        if min > max {
+            panic!("min must be > max (got: min {} and max {})", min, max);
+        }
+
+        let date = self.current_date;
+
+        let mut expiring_quotes: Vec<&Quote> = self.quotes
+            .iter()
+            .map(|(_, q)| q)
+            .into_iter()
+            .filter(|q| {
+                // TODO: should these be >= and <= ?
+                q.days_to_expiration(date) > min && q.days_to_expiration(date) < max
+            })
+            .collect();
+
+        expiring_quotes.sort_by_key(|a| {
+            (a.days_to_expiration(date), a.is_call(), a.strike_price())
+        });
+
+        expiring_quotes
+    }
+
+    // pub fn calls_expiring_after_n_days(&self, days: i32) -> Vec<&Quote> {
+    //     self.nearest_quotes_expiring_after_n_days(days)
+    //         .into_iter()
+    //         .filter(|q| q.is_call())
+    //         .collect()
+    // }
+
+    // pub fn puts_expiring_after_n_days(&self, days: i32) -> Vec<&Quote> {
+    //     self.nearest_quotes_expiring_after_n_days(days)
+    //         .into_iter()
+    //         .filter(|q| q.is_put())
+    //         .collect()
+    // }
+
+    #[allow(dead_code)]
+    pub fn call_quotes_for(&self, symbol: &str) -> Vec<&Quote> {
+        let mut quotes: Vec<&Quote> = self.quotes_for(symbol)
+            .into_iter()
+            .filter(|q| q.is_call())
+            .collect();
+        quotes.sort_by(|a, b| a.name().cmp(&b.name()));
+
+        quotes
+    }
+
+    #[allow(dead_code)]
+    pub fn put_quotes_for(&self, symbol: &str) -> Vec<&Quote> {
+        let mut quotes: Vec<&Quote> = self.quotes_for(symbol)
+            .into_iter()
+            .filter(|q| q.is_put())
+            .collect();
+        quotes.sort_by(|a, b| a.name().cmp(&b.name()));
+
+        quotes
+    }
+
+    pub fn process_simulation_data(&mut self) -> bool {
+        info!("===== new day ==========================================");
+
+        self.update_statistics();
+
+        self.quotes =
+            FnvHashMap::with_capacity_and_hasher(self.quote_map_capacity, Default::default());
+
+        // if we aborted the main loop when a quote for the next day came in, apply it now
+        if self.carried_over_quote.is_some() {
+            let quote = self.carried_over_quote.clone().unwrap();
+
+            // the model is done with the last day's quotes, so anything going
+            // ex today can be applied before today's quotes come in
+            self.process_corporate_actions(quote.date());
+
+            self.underlying_prices
+                .insert(quote.symbol(), quote.underlying_price());
+            self.current_date = quote.date();
+
+            self.quotes.insert(quote.name(), quote.clone());
+            self.quotes_processed += 1;
+
+            self.carried_over_quote = None;
+
+            debug!("inserted carried over data");
+        }
+
+        // manually consume the first quote here so we don't have to check
+        // to see if it's the first quote every single time
+        {
+            let first_quote = self.data_feed.next_quote().unwrap();
+            self.current_date = first_quote.date();
+            self.quotes.insert(first_quote.name(), first_quote);
+        }
+
+        while let Some(quote) = self.data_feed.next_quote() {
+            let day_changed = quote.date() != self.current_date;
+
+            if day_changed {
+                debug!("day changed from {} to {}", quote.date(), self.current_date);
+                // stops, take profits, etc. get the first look at the day's
+                // quotes so the model sees their results.
+                self.process_working_orders();
+                self.process_early_assignments(quote.date());
+
+                // settle anything that is expiring and that the model didn't
+                // already close the last trading day.  do this before we
+                // reset the quotes so that the last trading day's quotes are
+                // used when settling positions.
+                self.process_expirations(quote.date());
+
+                self.accrue_interest(quote.date());
+
+                self.update_statistics();
+                self.check_maintenance_margin();
+                self.check_circuit_breakers();
+                self.record_daily_pnl();
+                self.record_equity_point();
+                self.record_daily_greeks();
+
+                let key_count = self.quotes.keys().len();
+                if key_count > self.quote_map_capacity {
+                    self.quote_map_capacity = key_count;
+                }
+
+                self.carried_over_quote = Some(quote);
+
+                return true;
+            }
+
+            // ----- next day --------------------------------------------------
+
+            // TODO: maybe check that the quotes are in chronological order here?
+            // TODO: record last_quote time on struct
+
+            self.underlying_prices
+                .insert(quote.symbol(), quote.underlying_price());
+            self.current_date = quote.date();
+
+            self.quotes.insert(quote.name(), quote);
+            self.quotes_processed += 1;
+        }
+
+        self.final_unrealized_account_balance = self.unrealized_account_balance();
+
+        self.close_all_open_positions();
+        self.record_daily_pnl();
+        self.record_equity_point();
+
+        false
+    }
+
+    // orders without an id were placed by the broker itself (i.e., to close
+    // out positions at the end of the simulation).  false if the order was
+    // rejected instead of filled.
+    fn fill_order(&mut self, order: Order, quote: &Quote, order_id: Option<u32>) -> bool {
+        // TODO: ensure that days remaining is > 0
+        //       since we only buy at end of day, if there are no days left
+        //       the the contract is _already_ expired.
+
+        // TODO: call OrderFiller's logic here and use the limit on the Order
+        // let fill_price = whatever;
+
+        let fill_price = quote.midpoint_price();
+
+        let mut filled_order = self.new_fill(order, quote, fill_price);
+
+        let commish = self.commission_schedule.commission_for(&filled_order);
+
+        filled_order.set_commission(commish);
+
+        match order_id {
+            Some(id) => {
+                let result = self.check_halted(&[&filled_order])
+                    .and_then(|_| self.check_risk_limits(&[&filled_order]))
+                    .and_then(|_| self.check_buying_power(&[&filled_order]));
+
+                if let Err(reason) = result {
+                    self.reject_order(id, reason);
+                    return false;
+                }
+
+                filled_order.set_order_id(id);
+                self.set_order_status(id, OrderStatus::Filled, None);
+            }
+            None => filled_order.set_closed_by_broker(),
+        }
+
+        self.apply_fill(filled_order, quote, JournalEntryKind::Premium);
+
+        true
+    }
+
+    fn fill_combo_order(&mut self, combo: ComboOrder, quotes: Vec<Quote>, order_id: u32) {
+        let combo_id = self.combo_count + 1;
+
+        let mut filled_orders: Vec<FilledOrder> = combo
+            .legs()
+            .iter()
+            .zip(quotes.iter())
+            .map(|(leg, quote)| {
+                // every leg fills at its own midpoint so the legs add up to
+                // the combo's net midpoint
+                let mut filled_order =
+                    self.new_fill(leg.order().clone(), quote, quote.midpoint_price());
+                filled_order.set_combo_id(combo_id);
+                filled_order.set_order_id(order_id);
+                filled_order
+            })
+            .collect();
+
+        // the combo is charged as a single ticket.  spread the commission over
+        // the legs that would pay commission on their own, by contract count,
+        // so each Position carries its share.  any rounding remainder goes to
+        // the first of them.
+        let commish = self.commission_schedule.commission_for_combo(&filled_orders);
+
+        let mut paying: Vec<usize> = (0..filled_orders.len())
+            .filter(|&i| self.commission_schedule.commission_for(&filled_orders[i]) > Money::zero())
+            .collect();
+
+        if paying.is_empty() {
+            paying = (0..filled_orders.len()).collect();
+        }
+
+        let paying_contracts: i32 = paying.iter().map(|&i| filled_orders[i].quantity()).sum();
+        let mut allocated = Money::zero();
+
+        for &i in paying.iter().skip(1) {
+            let share = Money::from_cents(
+                commish.raw_value() * filled_orders[i].quantity() / paying_contracts,
+            );
+            filled_orders[i].set_commission(share);
+            allocated += share;
+        }
+
+        filled_orders[paying[0]].set_commission(commish - allocated);
+
+        let result = {
+            let legs: Vec<&FilledOrder> = filled_orders.iter().collect();
+            self.check_halted(&legs)
+                .and_then(|_| self.check_risk_limits(&legs))
+                .and_then(|_| self.check_buying_power(&legs))
+        };
+
+        if let Err(reason) = result {
+            self.reject_order(order_id, reason);
+            return;
+        }
+
+        self.combo_count = combo_id;
+        self.set_order_status(order_id, OrderStatus::Filled, None);
+
+        info!(
+            "== COMBO #{} FILLED @ {} net: {}",
+            combo_id,
+            combo.net_midpoint_price(&quotes),
+            combo.summary(),
+        );
+
+        for (filled_order, quote) in filled_orders.into_iter().zip(quotes.iter()) {
+            self.apply_fill(filled_order, quote, JournalEntryKind::Premium);
+        }
+    }
+
+    // a fill on today's date.  contracts that were adjusted by a corporate
+    // action deliver a different number of shares, so fills that add to (or
+    // close) an adjusted position take its multiplier before they're checked.
+    fn new_fill(&self, order: Order, quote: &Quote, price: Money) -> FilledOrder {
+        let mut filled_order = FilledOrder::new(order, quote, price, self.current_date);
+        let multiplier = self.multiplier_for(&filled_order);
+        filled_order.set_multiplier(multiplier);
+        filled_order
+    }
+
+    fn apply_fill(&mut self, filled_order: FilledOrder, quote: &Quote, kind: JournalEntryKind) {
+        let action = if filled_order.is_buy() { "buy" } else { "sell" };
+        let sign = if filled_order.is_buy() { ">>" } else { "<<" };
+        let call = if quote.is_call() { "CALL" } else { "PUT" };
+
+        let total = filled_order.cost_basis() + filled_order.commission();
+        let original_balance = self.unrealized_account_balance();
+
+        let cost_basis = filled_order.canonical_cost_basis();
+        let key = filled_order.position_key();
+        let group = filled_order.group();
+        let quantity = filled_order.quantity();
+        let commish = filled_order.commission();
+        let fill_price = filled_order.fill_price();
+
+        let reference: Rc<str> = Rc::from(filled_order.summary().as_str());
+        let filled_order_rc = Rc::from(filled_order);
+
+        self.filled_orders.push(Rc::clone(&filled_order_rc));
+
+        let lot_matching = self.lot_matching;
+
+        // stick the FilledOrder onto the Position
+        self.positions
+            .entry(key)
+            .or_insert_with(|| {
+                Position::new(&quote)
+                    .with_group(group)
+                    .with_lot_matching(lot_matching)
+            })
+            .apply_order(filled_order_rc);
+
+        self.post(kind, cost_basis, Rc::clone(&reference));
+        self.post(JournalEntryKind::Commission, Money::zero() - commish, reference);
+        self.commission_paid += commish;
+
+        // ===== print details ==========================================================
+
+        debug!(
+            "  {}ing contracts @ {} + {} commission ({} total)",
+            action,
+            fill_price,
+            commish,
+            total,
+        );
+
+        info!("{} {} ORDER FILLED.", sign, call);
+        info!(
+            "Strike: {} - Commission: {} - Old (un)balance: {} - New (un)balance: {}",
+            quote.strike_price(),
+            commish,
+            original_balance,
+            self.unrealized_account_balance(),
+        );
+        info!(
+            "   Underlying: {} - Bid: {} - Ask: {} - Expiration: {} days - {} contracts",
+            self.underlying_price_for("AAPL"),
+            quote.bid(),
+            quote.ask(),
+            quote.days_to_expiration(self.current_date),
+            quantity,
+        );
+    }
+
+    // ===== equity curve ===============================================================
+
+    fn record_equity_point(&mut self) {
+        let equity = self.unrealized_account_balance();
+        let open_positions = self.open_positions().len() + self.open_stock_positions().len();
+
+        let point = EquityPoint::new(
+            self.current_date,
+            self.balance,
+            equity - self.balance,
+            self.margin_requirement().initial(),
+            open_positions,
+        );
+
+        self.equity_curve.push(point);
+
+        for (symbol, price) in self.underlying_prices.iter() {
+            self.underlying_closes
+                .entry(Rc::clone(symbol))
+                .or_insert(vec![])
+                .push((self.current_date, *price));
+        }
+    }
+
+    // the close of every trading day, the last one after everything has been
+    // closed out
+    pub fn equity_curve(&self) -> &Vec<EquityPoint> {
+        &self.equity_curve
+    }
+
+    // every underlying the broker has seen a quote for, in alphabetical order
+    pub fn underlying_symbols(&self) -> Vec<Rc<str>> {
+        let mut symbols: Vec<Rc<str>> = self.underlying_closes.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    // the underlying price at the close of each trading day from when it was
+    // first quoted
+    pub fn underlying_closes(&self, symbol: &str) -> Option<&Vec<(DateTime<Utc>, Money)>> {
+        self.underlying_closes.get(symbol)
+    }
+
+    // ===== p&l ========================================================================
+
+    // takes a snapshot of every position that was open at some point today
+    // and of the account as a whole
+    fn record_daily_pnl(&mut self) {
+        let today = self.current_date.num_days_from_ce();
+        let mut unrealized = Money::zero();
+        let mut snapshots = vec![];
+
+        for p in self.positions() {
+            let traded_today = match p.orders().last() {
+                Some(o) => o.fill_date().num_days_from_ce() == today,
+                None => false,
+            };
+
+            if !p.is_open() && !traded_today {
+                continue;
+            }
+
+            let position_unrealized = self.unrealized_profit_for(p);
+            unrealized += position_unrealized;
+
+            let snapshot = PnlSnapshot::new(
+                self.current_date,
+                p.realized_profit() - p.commission_paid(),
+                position_unrealized,
+            );
+
+            snapshots.push((p.key(), snapshot));
+        }
+
+        for s in self.open_stock_positions() {
+            let price = self.underlying_price_for(&s.symbol());
+            unrealized += s.market_value(price) - s.cost_basis();
+        }
+
+        // everything that isn't unrealized (including commission, interest
+        // and dividends) has been realized
+        let total = self.unrealized_account_balance() - self.opening_balance;
+
+        for (key, snapshot) in snapshots {
+            self.position_pnl.entry(key).or_insert(vec![]).push(snapshot);
+        }
+
+        self.account_pnl
+            .push(PnlSnapshot::new(self.current_date, total - unrealized, unrealized));
+    }
+
+    // open contracts marked to market with the broker's MarkMethod
+    pub fn unrealized_profit_for(&self, position: &Position) -> Money {
+        if position.is_open() {
+            position.unrealized_profit(&self.mark_for(position), self.mark_method)
+        } else {
+            Money::zero()
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn account_pnl_history(&self) -> &Vec<PnlSnapshot> {
+        &self.account_pnl
+    }
+
+    // net of the position's commission
+    #[allow(dead_code)]
+    pub fn position_pnl_history(&self, position: &Position) -> Option<&Vec<PnlSnapshot>> {
+        self.position_pnl.get(&position.key())
+    }
+
+    // ===== greeks =====================================================================
+
+    pub fn beta_for(&self, symbol: &str) -> f64 {
+        match self.betas.get(symbol) {
+            Some(beta) => *beta,
+            None => 1.0,
+        }
+    }
+
+    pub fn greeks_for(&self, position: &Position) -> Greeks {
+        if !position.is_open() {
+            return Greeks::zero();
+        }
+
+        Greeks::option(
+            &self.mark_for(position),
+            position.quantity(),
+            position.multiplier(),
+            self.current_date,
+            self.risk_free_rate,
+            self.beta_for(&position.symbol()),
+        )
+    }
+
+    // options and shares on a single underlying
+    pub fn greeks_for_symbol(&self, symbol: &str) -> Greeks {
+        let options = self.open_positions()
+            .into_iter()
+            .filter(|p| &*p.symbol() == symbol)
+            .fold(Greeks::zero(), |total, p| total + self.greeks_for(p));
+
+        match self.stock_positions.get(symbol) {
+            Some(s) if s.is_open() => {
+                let price = self.underlying_price_for(symbol);
+                options + Greeks::stock(s.quantity(), price, self.beta_for(symbol))
+            }
+            _ => options,
+        }
+    }
+
+    pub fn portfolio_greeks(&self) -> Greeks {
+        let mut symbols: Vec<Rc<str>> = self.open_positions()
+            .iter()
+            .map(|p| p.symbol())
+            .chain(self.open_stock_positions().iter().map(|s| s.symbol()))
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+
+        symbols
+            .iter()
+            .fold(Greeks::zero(), |total, s| total + self.greeks_for_symbol(s))
+    }
+
+    fn record_daily_greeks(&mut self) {
+        let greeks = self.portfolio_greeks();
+
+        debug!("greeks: {}", greeks.summary());
+
+        self.greeks_history.push((self.current_date, greeks));
+    }
+
+    pub fn greeks_history(&self) -> &Vec<(DateTime<Utc>, Greeks)> {
+        &self.greeks_history
+    }
+
+    // the change in the account's total P&L from one trading day to the next
+    pub fn daily_account_pnl(&self) -> Vec<(DateTime<Utc>, Money)> {
+        let mut previous = Money::zero();
+
+        self.account_pnl
+            .iter()
+            .map(|s| {
+                let change = s.total() - previous;
+                previous = s.total();
+                (s.date(), change)
+            })
+            .collect()
+    }
+
+    // ===== interest ===================================================================
+
+    // credits or charges interest on the cash balance for every calendar day
+    // from today until the next trading day (actual/360)
+    fn accrue_interest(&mut self, next_date: DateTime<Utc>) {
+        let days = next_date.num_days_from_ce() - self.current_date.num_days_from_ce();
+
+        if days <= 0 {
+            return;
+        }
+
+        let schedule = if self.balance > Money::zero() {
+            &self.credit_interest_rate
+        } else if self.balance < Money::zero() {
+            &self.debit_interest_rate
+        } else {
+            return;
+        };
+
+        let rate = match *schedule {
+            Some(ref r) => r.annual_rate_on(self.current_date),
+            None => return,
+        };
+
+        let cents = self.balance.raw_value() as f64 * rate * days as f64 / 360.0;
+        let amount = Money::from_cents(cents.round() as i32);
+
+        if amount == Money::zero() {
+            return;
+        }
+
+        debug!(
+            "interest of {} on {} at {:.3}% for {} days",
+            amount,
+            self.balance,
+            rate * 100.0,
+            days,
+        );
+
+        self.interest_accruals.push(InterestAccrual::new(
+            self.current_date,
+            self.balance,
+            rate,
+            days,
+            amount,
+        ));
+
+        let reference = format!("{:.3}% on {} for {} days", rate * 100.0, self.balance, days);
+        self.post(JournalEntryKind::Interest, amount, Rc::from(reference.as_str()));
+    }
+
+    pub fn interest_accruals(&self) -> &Vec<InterestAccrual> {
+        &self.interest_accruals
+    }
+
+    pub fn interest_earned(&self) -> Money {
+        self.interest_accruals
+            .iter()
+            .filter(|a| a.is_credit())
+            .map(|a| a.amount())
+            .sum()
+    }
+
+    pub fn interest_paid(&self) -> Money {
+        Money::zero()
+            - self.interest_accruals
+                .iter()
+                .filter(|a| !a.is_credit())
+                .map(|a| a.amount())
+                .sum()
+    }
+
+    // ===== margin =====================================================================
+
+    // everything held, valued at what it could be liquidated for
+    pub fn holdings(&self) -> Vec<Holding> {
+        let mut holdings: Vec<Holding> = self.open_positions()
+            .iter()
+            .map(|p| {
+                let quote = self.mark_for(p);
+                let price = self.mark_method.price(&quote, p.is_long());
+                Holding::option(&quote, p.quantity(), price, p.multiplier())
+            })
+            .collect();
+
+        for s in self.open_stock_positions() {
+            let price = self.underlying_price_for(&s.symbol());
+            holdings.push(Holding::stock(s.symbol(), s.quantity(), price, self.current_date));
+        }
+
+        holdings
+    }
+
+    // what holdings() would be if these orders were filled
+    fn holdings_after(&self, filled_orders: &[&FilledOrder]) -> Vec<Holding> {
+        let mut holdings = self.holdings();
+
+        for o in filled_orders {
+            let existing = holdings.iter().position(|h| h.name() == o.option_name());
+
+            match existing {
+                Some(i) => {
+                    let quantity = holdings[i].quantity() + o.canonical_quantity();
+                    let price = self.mark_method.price(o.quote(), quantity > 0);
+                    let multiplier = holdings[i].multiplier();
+                    holdings[i] = Holding::option(o.quote(), quantity, price, multiplier);
+                }
+                None => {
+                    let quantity = o.canonical_quantity();
+                    let price = self.mark_method.price(o.quote(), quantity > 0);
+                    holdings.push(Holding::option(o.quote(), quantity, price, o.multiplier()));
+                }
+            }
+        }
+
+        holdings
+    }
+
+    pub fn margin_requirement(&self) -> MarginRequirement {
+        self.margin_calculator.requirement_for(&self.holdings())
+    }
+
+    // equity that isn't tied up by the initial margin requirement
+    pub fn buying_power(&self) -> Money {
+        self.unrealized_account_balance() - self.margin_requirement().initial()
+    }
+
+    // orders that open (or add to) positions need enough equity left over to
+    // cover the initial requirement of the account after they're filled.
+    // orders that only close positions are always allowed.
+    fn check_buying_power(&self, filled_orders: &[&FilledOrder]) -> Result<(), String> {
+        if filled_orders.iter().all(|o| o.is_close()) {
+            return Ok(());
+        }
+
+        let holdings = self.holdings_after(filled_orders);
+        let requirement = self.margin_calculator.requirement_for(&holdings);
+
+        let cash_change: Money = filled_orders
+            .iter()
+            .map(|o| o.canonical_cost_basis() - o.commission())
+            .sum();
+
+        let market_value: Money = holdings.iter().map(|h| h.market_value()).sum();
+        let equity = self.balance + cash_change + market_value;
+
+        if equity < requirement.initial() {
+            return Err(format!(
+                "not enough buying power ({} requirement, {} equity)",
+                requirement.initial(),
+                equity,
+            ));
+        }
+
+        Ok(())
+    }
+
+    // ===== circuit breakers ===========================================================
+
+    fn check_circuit_breakers(&mut self) {
+        if self.circuit_breakers.is_empty() {
+            return;
+        }
+
+        let equity = self.unrealized_account_balance();
+        let peak = self.highest_unrealized_account_balance;
+
+        let drawdown = (peak - equity).raw_value() as f64 / peak.raw_value() as f64 * 100.0;
+
+        let previous_close = match self.account_pnl.last() {
+            Some(s) => self.opening_balance + s.total(),
+            None => self.opening_balance,
+        };
+
+        let margin_utilization = if equity > Money::zero() {
+            self.margin_requirement().initial().raw_value() as f64 / equity.raw_value() as f64
+                * 100.0
+        } else {
+            f64::INFINITY
+        };
+
+        let breakers = mem::take(&mut self.circuit_breakers);
+        let mut liquidate = false;
+
+        for b in breakers {
+            match b.check(drawdown, equity - previous_close, margin_utilization) {
+                Some(reason) => {
+                    warn!("!! CIRCUIT BREAKER ({}): {}", b.action().name(), reason);
+
+                    liquidate = liquidate || b.action() == BreakerAction::Liquidate;
+
+                    self.circuit_breaker_events
+                        .push(CircuitBreakerEvent::new(self.current_date, b.action(), reason));
+                }
+                None => self.circuit_breakers.push(b),
+            }
+        }
+
+        if liquidate {
+            self.close_all_open_positions();
+        }
+    }
+
+    // true once a circuit breaker has tripped, after which positions can only
+    // be closed
+    pub fn is_halted(&self) -> bool {
+        !self.circuit_breaker_events.is_empty()
+    }
+
+    pub fn circuit_breaker_events(&self) -> &Vec<CircuitBreakerEvent> {
+        &self.circuit_breaker_events
+    }
+
+    fn check_halted(&self, filled_orders: &[&FilledOrder]) -> Result<(), String> {
+        if !self.is_halted() || filled_orders.iter().all(|o| o.is_close()) {
+            return Ok(());
+        }
+
+        Err(format!(
+            "circuit breaker: {}",
+            self.circuit_breaker_events[0].reason()
+        ))
+    }
+
+    // see RiskLimits.  like buying power, orders that only close positions
+    // are always allowed.
+    fn check_risk_limits(&self, filled_orders: &[&FilledOrder]) -> Result<(), String> {
+        if filled_orders.iter().all(|o| o.is_close()) {
+            return Ok(());
+        }
+
+        let limits = &self.risk_limits;
+
+        let contracts = match filled_orders.iter().map(|o| o.quantity()).max() {
+            Some(contracts) => contracts,
+            None => return Ok(()),
+        };
+        risk_limits::check(limits.max_contracts_per_order(), contracts, "contracts per order")?;
+
+        for o in filled_orders.iter().filter(|o| o.is_open()) {
+            let held = match self.positions.get(&o.position_key()) {
+                Some(p) => p.quantity(),
+                None => 0,
+            };
+
+            risk_limits::check(
+                limits.max_contracts_per_position(),
+                (held + o.canonical_quantity()).abs(),
+                &format!("contracts of {}", o.option_name()),
+            )?;
+        }
+
+        let holdings = self.holdings_after(filled_orders);
+        let equity = self.unrealized_account_balance();
+
+        for o in filled_orders.iter().filter(|o| o.is_open()) {
+            let notional: Money = holdings
+                .iter()
+                .filter(|h| &*h.symbol() == o.symbol())
+                .map(|h| h.underlying_price() * h.multiplier() * h.quantity().abs())
+                .sum();
+
+            risk_limits::check(
+                limits.max_notional_per_underlying(),
+                notional,
+                &format!("notional on {}", o.symbol()),
+            )?;
+
+            let expiration = o.quote().expiration_date();
+
+            // there's no percent of nothing, and nothing should be opened then
+            if limits.max_expiration_percent().is_some() && equity <= Money::zero() {
+                return Err(format!(
+                    "risk limit: no percent of the account can expire {} with {} equity",
+                    expiration.format("%F"),
+                    equity,
+                ));
+            }
+
+            let gross: Money = holdings
+                .iter()
+                .filter(|h| !h.is_stock() && h.expiration_date() == expiration)
+                .map(|h| absolute(h.market_value()))
+                .sum();
+            let percent = gross.raw_value() as f64 / equity.raw_value() as f64 * 100.0;
+
+            risk_limits::check(
+                limits.max_expiration_percent(),
+                rounded(percent),
+                &format!("percent of the account expiring {}", expiration.format("%F")),
+            )?;
+        }
+
+        // exposures that are already over the limit can still be reduced
+        let before = self.portfolio_greeks();
+        let after = filled_orders.iter().fold(before, |total, o| {
+            let multiplier = self.multiplier_for(o);
+
+            total + Greeks::option(
+                o.quote(),
+                o.canonical_quantity(),
+                multiplier,
+                self.current_date,
+                self.risk_free_rate,
+                self.beta_for(o.symbol()),
+            )
+        });
+
+        if after.dollar_delta().abs() > before.dollar_delta().abs() {
+            risk_limits::check(
+                limits.max_portfolio_delta(),
+                rounded(after.dollar_delta().abs()),
+                "portfolio dollar delta",
+            )?;
+        }
+
+        if after.vega().abs() > before.vega().abs() {
+            risk_limits::check(
+                limits.max_portfolio_vega(),
+                rounded(after.vega().abs()),
+                "portfolio vega",
+            )?;
+        }
+
+        if let Some(limit) = limits.max_loss_per_trade() {
+            let legs: Vec<(&FilledOrder, i32)> = filled_orders
+                .iter()
+                .map(|&o| (o, self.multiplier_for(o)))
+                .collect();
+
+            match risk_limits::max_loss(&legs) {
+                Some(loss) => risk_limits::check(Some(limit), loss, "max loss")?,
+                None => {
+                    return Err(format!(
+                        "risk limit: max loss is unlimited, over the limit of {}",
+                        limit,
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // the shares each contract of the order delivers, which follows the
+    // position it adds to
+    fn multiplier_for(&self, filled_order: &FilledOrder) -> i32 {
+        match self.positions.get(&filled_order.position_key()) {
+            Some(p) => p.multiplier(),
+            None => filled_order.multiplier(),
+        }
+    }
+
+    fn reject_order(&mut self, id: u32, reason: String) {
+        info!(
+            "!! ORDER #{} REJECTED: {} - {}",
+            id,
+            self.submitted_orders[id as usize - 1].summary(),
+            reason,
+        );
+
+        self.set_order_status(id, OrderStatus::Rejected, Some(reason));
+    }
+
+    fn check_maintenance_margin(&mut self) {
+        let requirement = self.margin_requirement();
+        let equity = self.unrealized_account_balance();
+
+        self.margin_usage.record(equity, requirement.initial());
+
+        if !self.margin_comparisons.is_empty() {
+            let holdings = self.holdings();
+
+            for c in self.margin_comparisons.iter_mut() {
+                let requirement = c.calculator.requirement_for(&holdings).initial();
+                c.usage.record(equity, requirement);
+            }
+        }
+
+        if equity < requirement.maintenance() {
+            let call = MarginCall::new(self.current_date, equity, requirement.maintenance());
+
+            warn!(
+                "!! MARGIN CALL: {} equity is {} short of the {} maintenance requirement",
+                equity,
+                call.deficit(),
+                requirement.maintenance(),
+            );
+
+            self.margin_calls.push(call);
+        }
+    }
+
+    pub fn margin_calls(&self) -> &Vec<MarginCall> {
+        &self.margin_calls
+    }
+
+    pub fn quotes_processed(&self) -> i32 {
+        self.quotes_processed
+    }
+
+    pub fn current_date(&self) -> DateTime<Utc> {
+        self.current_date
+    }
+
+    pub fn account_balance(&self) -> Money {
+        self.balance
+    }
+
+    pub fn unrealized_account_balance(&self) -> Money {
+        self.balance
+            + self.open_positions()
+                .iter()
+                .map(|p| p.market_value(&self.mark_for(p), self.mark_method))
+                .sum()
+            + self.open_stock_positions()
+                .iter()
+                .map(|s| s.market_value(self.underlying_price_for(&s.symbol())))
+                .sum()
+    }
+
+    pub fn quote_for(&self, option_name: Rc<str>) -> Option<Quote> {
+        match self.quotes.get(&option_name) {
+            Some(q) => Some(q.clone()),
+            None => None,
+        }
+    }
+
+    // the position's quote or, for series that aren't quoted (like contracts
+    // left with an odd deliverable by a split), one priced at intrinsic value
+    fn mark_for(&self, position: &Position) -> Quote {
+        if let Some(q) = self.quote_for(position.name()) {
+            return q;
+        }
+
+        let underlying_price = self.underlying_price_for(&position.symbol());
+
+        let intrinsic_value = if position.is_call() {
+            underlying_price - position.strike_price()
+        } else {
+            position.strike_price() - underlying_price
+        };
+        let price = if intrinsic_value > Money::zero() {
+            intrinsic_value
+        } else {
+            Money::zero()
+        };
+
+        Quote::new(
+            position.symbol().to_string(),
+            position.expiration_date(),
+            price,
+            price,
+            price,
+            position.is_call(),
+            position.strike_price(),
+            0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0,
+            underlying_price,
+            self.current_date,
+        )
+    }
+
+    pub fn process_order_request(&mut self, request: OrderRequest) {
+        match request {
+            OrderRequest::Single(order) => self.process_order(order),
+            OrderRequest::Combo(combo) => self.process_combo_order(combo),
+            OrderRequest::Conditional(order) => self.submit_conditional_order(order, None),
+            OrderRequest::OneCancelsOther(orders) => {
+                self.oco_group_count += 1;
+                let group = self.oco_group_count;
+
+                for o in orders {
+                    self.submit_conditional_order(o, Some(group));
+                }
+            }
+        }
+    }
+
+    fn submit_conditional_order(&mut self, order: ConditionalOrder, oco_group: Option<u32>) {
+        let id = self.submit(order.order().symbol(), vec![order.option_name()], order.summary());
+
+        info!("working order #{} accepted: {}", id, order.summary());
+
+        self.working_orders.push(WorkingOrder {
+            id: id,
+            oco_group: oco_group,
+            order: order,
+        });
+    }
+
+    #[allow(dead_code)]
+    pub fn working_orders(&self) -> Vec<&ConditionalOrder> {
+        self.working_orders.iter().map(|w| &w.order).collect()
+    }
+
+    fn has_open_position(&self, key: &PositionKey) -> bool {
+        match self.positions.get(key) {
+            Some(p) => p.is_open(),
+            None => false,
+        }
+    }
+
+    fn process_working_orders(&mut self) {
+        let working_orders = mem::take(&mut self.working_orders);
+        let mut filled_oco_groups: Vec<u32> = vec![];
+
+        for mut w in working_orders {
+            if let Some(group) = w.oco_group {
+                if filled_oco_groups.contains(&group) {
+                    info!("working order #{} cancelled by its OCO sibling", w.id);
+                    self.cancel_order(w.id, "OCO sibling filled");
+                    continue;
+                }
+            }
+
+            // nothing left to close (the model or another order beat us to it)
+            let key = (w.order.order().group(), w.order.option_name());
+            if w.order.order().is_close() && !self.has_open_position(&key) {
+                info!("working order #{} cancelled, position is closed", w.id);
+                self.cancel_order(w.id, "position is closed");
+                continue;
+            }
+
+            let quote = match self.quote_for(w.order.option_name()) {
+                Some(q) => q,
+                None => {
+                    self.working_orders.push(w);
+                    continue;
+                }
+            };
+
+            if w.order.evaluate(&quote) {
+                info!("working order #{} triggered: {}", w.id, w.order.summary());
+
+                // a rejected leg leaves its siblings working so the position
+                // keeps the rest of its bracket
+                if self.fill_order(w.order.order().clone(), &quote, Some(w.id)) {
+                    if let Some(group) = w.oco_group {
+                        filled_oco_groups.push(group);
+                    }
+                }
+            } else {
+                self.working_orders.push(w);
+            }
+        }
+
+        // siblings that were already looked at before their group filled
+        let (cancelled, working): (Vec<WorkingOrder>, Vec<WorkingOrder>) =
+            mem::take(&mut self.working_orders)
+                .into_iter()
+                .partition(|w| match w.oco_group {
+                    Some(group) => filled_oco_groups.contains(&group),
+                    None => false,
+                });
+
+        for w in cancelled {
+            info!("working order #{} cancelled by its OCO sibling", w.id);
+            self.cancel_order(w.id, "OCO sibling filled");
+        }
+
+        self.working_orders = working;
+    }
+
+    pub fn process_order(&mut self, order: Order) {
+        let id = self.submit(order.symbol(), vec![order.option_name()], order.summary());
+
+        match self.quote_for(order.option_name()) {
+            Some(quote) => {
+                self.fill_order(order, &quote, Some(id));
+            }
+            None => self.reject_order(id, "no quote for the option".to_string()),
+        }
+    }
+
+    pub fn process_combo_order(&mut self, combo: ComboOrder) {
+        if combo.legs().is_empty() {
+            panic!("combo order has no legs");
+        }
+
+        let id = {
+            let names = combo.legs().iter().map(|l| l.option_name()).collect();
+            self.submit(combo.legs()[0].order().symbol(), names, combo.summary())
+        };
+
+        let quotes: Option<Vec<Quote>> = combo
+            .legs()
+            .iter()
+            .map(|l| self.quote_for(l.option_name()))
+            .collect();
+
+        let quotes = match quotes {
+            Some(quotes) => quotes,
+            None => {
+                self.reject_order(id, "no quote for a leg".to_string());
+                return;
+            }
+        };
+
+        if !combo.is_marketable(&quotes) {
+            info!(
+                "natural market {} / {} is outside the limit, not filling {}",
+                combo.net_ask(&quotes),
+                combo.net_bid(&quotes),
+                combo.summary(),
+            );
+            self.cancel_order(id, "natural market outside the limit");
+            return;
+        }
+
+        self.fill_combo_order(combo, quotes, id);
+    }
+
+    // ids of every combo that has been filled, in fill order
+    pub fn combo_ids(&self) -> Vec<u32> {
+        (1..self.combo_count + 1).collect()
+    }
+
+    // the positions opened or closed by the legs of a single combo
+    pub fn positions_for_combo(&self, combo_id: u32) -> Vec<&Position> {
+        self.positions()
+            .into_iter()
+            .filter(|p| p.orders().iter().any(|o| o.combo_id() == Some(combo_id)))
+            .collect()
+    }
+
+    pub fn open_positions(&self) -> Vec<&Position> {
+        let mut ps: Vec<&Position> = self.positions
+            .iter()
+            .map(|(_, p)| p)
+            .filter(|p| p.is_open())
+            .collect();
+        ps.sort_by(|a, b| a.name().cmp(&b.name()));
+        ps
+    }
+
+    pub fn positions(&self) -> Vec<&Position> {
+        let mut ps: Vec<&Position> = self.positions.iter().map(|(_, p)| p).collect();
+        ps.sort_by(|a, b| a.name().cmp(&b.name()));
+        ps
+    }
+
+    // ===== trade groups ===============================================================
+
+    // every group any strategy has traded in, open or not
+    pub fn all_trade_groups(&self) -> Vec<TradeGroup> {
+        let mut groups: Vec<TradeGroup> =
+            self.positions.values().filter_map(|p| p.group()).collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+
+    // every group the strategy has traded in, open or not, in id order
+    pub fn trade_groups(&self, strategy: &str) -> Vec<TradeGroup> {
+        self.all_trade_groups()
+            .into_iter()
+            .filter(|g| &*g.strategy() == strategy)
+            .collect()
+    }
+
+    // the groups that still have at least one open position
+    pub fn open_trade_groups(&self, strategy: &str) -> Vec<TradeGroup> {
+        self.trade_groups(strategy)
+            .into_iter()
+            .filter(|g| !self.open_positions_in_group(g).is_empty())
+            .collect()
+    }
+
+    pub fn positions_in_group(&self, group: &TradeGroup) -> Vec<&Position> {
+        self.positions()
+            .into_iter()
+            .filter(|p| p.is_in_group(group))
+            .collect()
+    }
+
+    pub fn open_positions_in_group(&self, group: &TradeGroup) -> Vec<&Position> {
+        self.open_positions()
+            .into_iter()
+            .filter(|p| p.is_in_group(group))
+            .collect()
+    }
+
+    // realized and unrealized, net of commission, across every leg of the group
+    pub fn trade_group_pnl(&self, group: &TradeGroup) -> Money {
+        self.positions_in_group(group)
+            .iter()
+            .map(|p| p.realized_profit() - p.commission_paid() + self.unrealized_profit_for(p))
+            .sum()
+    }
+
+    // ===== trades =====================================================================
+
+    // every round trip that has gone back to flat, in the order they closed.
+    // the contracts held through a whole-number split are counted in
+    // post-split contracts from then on.
+    pub fn trades(&self) -> Vec<Trade> {
+        let mut trades = vec![];
+
+        for p in self.positions() {
+            let history = self.position_pnl_history(p);
+
+            // the realized profit of the trades before this one
+            let mut baseline = Money::zero();
+
+            let mut quantity = 0;
+            let mut contracts = 0;
+            let mut cash = Money::zero();
+            let mut commission = Money::zero();
+            let mut first: Option<Rc<FilledOrder>> = None;
+
+            for (i, o) in p.orders().iter().enumerate() {
+                for &(_, ratio) in p.contract_splits().iter().filter(|&&(at, _)| at == i) {
+                    quantity *= ratio;
+                    contracts *= ratio;
+                }
+
+                if first.is_none() {
+                    first = Some(Rc::clone(o));
+                }
+
+                quantity += o.canonical_quantity();
+                contracts = contracts.max(quantity.abs());
+                cash += o.canonical_cost_basis();
+                commission += o.commission();
+
+                if quantity != 0 {
+                    continue;
+                }
+
+                let opening = first.take().unwrap();
+                let profit = cash - commission;
+
+                let opened = opening.fill_date().num_days_from_ce();
+                let closed = o.fill_date().num_days_from_ce();
+
+                let excursions: Vec<Money> = history
+                    .map(|h| {
+                        h.iter()
+                            .filter(|s| {
+                                let day = s.date().num_days_from_ce();
+                                day >= opened && day <= closed
+                            })
+                            .map(|s| s.total() - baseline)
+                            .collect()
+                    })
+                    .unwrap_or(vec![]);
+
+                let adverse = excursions.iter().fold(Money::zero(), |m, &e| m.min(e));
+                let favorable = excursions.iter().fold(Money::zero(), |m, &e| m.max(e));
+
+                trades.push(Trade::new(
+                    p.name(),
+                    p.group(),
+                    opening.fill_date(),
+                    o.fill_date(),
+                    opening.is_buy(),
+                    contracts,
+                    profit,
+                    commission,
+                    adverse,
+                    favorable,
+                    o.closed_by_broker(),
+                ));
+
+                baseline += profit;
+                contracts = 0;
+                cash = Money::zero();
+                commission = Money::zero();
+            }
+        }
+
+        trades.sort_by_key(|t| t.closed());
+        trades
+    }
+
+    // every lot closed so far, in the order they were closed
+    pub fn closed_lots(&self) -> Vec<&ClosedLot> {
+        let mut lots: Vec<&ClosedLot> =
+            self.positions.values().flat_map(|p| p.closed_lots().iter()).collect();
+        lots.sort_by_key(|l| l.closed());
+        lots
+    }
+
+    // realized profit (before commission) from lots held for `period`
+    pub fn realized_lot_profit(&self, period: HoldingPeriod) -> Money {
+        self.closed_lots()
+            .iter()
+            .filter(|l| l.holding_period() == period)
+            .map(|l| l.realized_profit())
+            .sum()
+    }
+
+    pub fn open_stock_positions(&self) -> Vec<&StockPosition> {
+        let mut ps: Vec<&StockPosition> =
+            self.stock_positions.values().filter(|p| p.is_open()).collect();
+        ps.sort_by_key(|p| p.symbol());
+        ps
+    }
+
+    pub fn stock_positions(&self) -> Vec<&StockPosition> {
+        let mut ps: Vec<&StockPosition> = self.stock_positions.values().collect();
+        ps.sort_by_key(|p| p.symbol());
+        ps
+    }
+
+    pub fn exercise_events(&self) -> &Vec<ExerciseEvent> {
+        &self.exercise_events
+    }
+
+    // ===== order history ==============================================================
+
+    // records an order from the model and returns its id
+    fn submit(&mut self, symbol: &str, option_names: Vec<Rc<str>>, summary: String) -> u32 {
+        let id = self.submitted_orders.len() as u32 + 1;
+
+        self.submitted_orders.push(SubmittedOrder::new(
+            id,
+            self.current_date,
+            Rc::from(symbol),
+            option_names,
+            summary,
+        ));
+
+        id
+    }
+
+    fn set_order_status(&mut self, id: u32, status: OrderStatus, reason: Option<String>) {
+        let date = self.current_date;
+
+        self.submitted_orders[id as usize - 1].set_status(status, date, reason);
+    }
+
+    fn cancel_order(&mut self, id: u32, reason: &str) {
+        self.set_order_status(id, OrderStatus::Cancelled, Some(reason.to_string()));
+    }
+
+    pub fn submitted_orders(&self) -> &Vec<SubmittedOrder> {
+        &self.submitted_orders
+    }
+
+    pub fn submitted_orders_with_status(&self, status: OrderStatus) -> Vec<&SubmittedOrder> {
+        self.submitted_orders
+            .iter()
+            .filter(|o| o.status() == status)
+            .collect()
+    }
+
+    pub fn rejected_orders(&self) -> Vec<&SubmittedOrder> {
+        self.submitted_orders_with_status(OrderStatus::Rejected)
+    }
+
+    #[allow(dead_code)]
+    pub fn submitted_orders_on(&self, date: DateTime<Utc>) -> Vec<&SubmittedOrder> {
+        self.submitted_orders
+            .iter()
+            .filter(|o| o.date().num_days_from_ce() == date.num_days_from_ce())
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn submitted_orders_for_symbol(&self, symbol: &str) -> Vec<&SubmittedOrder> {
+        self.submitted_orders
+            .iter()
+            .filter(|o| &*o.symbol() == symbol)
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn submitted_orders_for_position(&self, option_name: &str) -> Vec<&SubmittedOrder> {
+        self.submitted_orders
+            .iter()
+            .filter(|o| o.is_for(option_name))
+            .collect()
+    }
+
+    // everything that was filled, including the broker's own closing orders
+    #[allow(dead_code)]
+    pub fn filled_orders(&self) -> &Vec<Rc<FilledOrder>> {
+        &self.filled_orders
+    }
+
+    #[allow(dead_code)]
+    pub fn filled_orders_on(&self, date: DateTime<Utc>) -> Vec<&Rc<FilledOrder>> {
+        self.filled_orders
+            .iter()
+            .filter(|o| o.fill_date().num_days_from_ce() == date.num_days_from_ce())
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn filled_orders_for_symbol(&self, symbol: &str) -> Vec<&Rc<FilledOrder>> {
+        self.filled_orders
+            .iter()
+            .filter(|o| o.symbol() == symbol)
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn filled_orders_for_position(&self, option_name: &str) -> Vec<&Rc<FilledOrder>> {
+        self.filled_orders
+            .iter()
+            .filter(|o| &*o.option_name() == option_name)
+            .collect()
+    }
+
+    pub fn total_order_count(&self) -> i32 {
+        self.filled_orders.len() as i32
+    }
+
+    pub fn commission_paid(&self) -> Money {
+        self.commission_paid
+    }
+
+    // shares > 0 is a buy and shares < 0 is a sell
+    fn apply_stock_trade(
+        &mut self,
+        symbol: Rc<str>,
+        shares: i32,
+        price: Money,
+        kind: JournalEntryKind,
+    ) {
+        let reference = format!("{} shares of {} @ {}", shares, symbol, price);
+
+        self.stock_positions
+            .entry(Rc::clone(&symbol))
+            .or_insert(StockPosition::new(symbol))
+            .apply_trade(shares, price);
+
+        self.post(kind, Money::zero() - price * shares, Rc::from(reference.as_str()));
+    }
+
+    // closes out the option position with a zero commission fill at `price`
+    fn close_for_settlement(&mut self, position: &Position, quote: &Quote, price: Money) {
+        let order = closing_order(position, quote, price);
+        let mut filled_order = self.new_fill(order, quote, price);
+        filled_order.set_closed_by_broker();
+
+        self.apply_fill(filled_order, quote, JournalEntryKind::Settlement);
+    }
+
+    // closes out the option and exchanges shares for cash at the strike.
+    // returns the shares received (> 0) or delivered (< 0) and the cash
+    // received (> 0) or paid (< 0) for them.
+    fn settle_by_delivery(&mut self, position: &Position, quote: &Quote) -> (i32, Money) {
+        // long calls and short puts receive shares, long puts and short calls
+        // deliver them
+        let shares = if quote.is_call() {
+            position.quantity() * position.multiplier()
+        } else {
+            -position.quantity() * position.multiplier()
+        };
+
+        self.close_for_settlement(position, quote, Money::zero());
+        self.apply_stock_trade(
+            position.symbol(),
+            shares,
+            quote.strike_price(),
+            JournalEntryKind::Settlement,
+        );
+
+        (shares, Money::zero() - quote.strike_price() * shares)
+    }
+
+    fn record_exercise_event(
+        &mut self,
+        position: &Position,
+        quote: &Quote,
+        kind: ExerciseKind,
+        shares: i32,
+        cash: Money,
+    ) {
+        let event = ExerciseEvent::new(
+            self.current_date,
+            position.name(),
+            position.symbol(),
+            kind,
+            position.quantity(),
+            quote.strike_price(),
+            quote.underlying_price(),
+            shares,
+            cash,
+        ).with_group(position.group());
+
+        info!("** {}", event.summary());
+
+        self.exercise_events.push(event);
+    }
+
+    // in-the-money options (by at least the auto exercise threshold) are
+    // exercised or assigned into shares at the strike, or settled in cash
+    // for index options.  everything else expires worthless.
+    fn process_expirations(&mut self, date: DateTime<Utc>) {
+        let expiring: Vec<Position> = self.open_positions()
+            .into_iter()
+            .filter(|p| p.is_expired(date))
+            .cloned()
+            .collect();
+
+        for position in expiring {
+            let quote = self.mark_for(&position);
+            let in_the_money_by = in_the_money_by(&quote);
+
+            let exercised = in_the_money_by >= self.auto_exercise_threshold
+                && in_the_money_by > Money::zero();
+
+            let (kind, shares, cash) = if !exercised {
+                self.close_for_settlement(&position, &quote, Money::zero());
+
+                (ExerciseKind::ExpiredWorthless, 0, Money::zero())
+            } else if self.is_cash_settled(&position.symbol()) {
+                // the option is closed out at its intrinsic value
+                self.close_for_settlement(&position, &quote, in_the_money_by);
+
+                let cash = in_the_money_by * position.multiplier() * position.quantity();
+
+                (ExerciseKind::CashSettled, 0, cash)
+            } else {
+                let (shares, cash) = self.settle_by_delivery(&position, &quote);
+
+                let kind = if position.is_long() {
+                    ExerciseKind::Exercised
+                } else {
+                    ExerciseKind::Assigned
+                };
+
+                (kind, shares, cash)
+            };
+
+            self.record_exercise_event(&position, &quote, kind, shares, cash);
+        }
+    }
+
+    // asks the early assignment model (if there is one) whether any short,
+    // in-the-money options get assigned before tomorrow's session
+    fn process_early_assignments(&mut self, next_date: DateTime<Utc>) {
+        if self.early_assignment.is_none() {
+            return;
+        }
+
+        // expiring positions are taken care of by process_expirations()
+        let candidates: Vec<Position> = self.open_positions()
+            .into_iter()
+            .filter(|p| p.is_short() && !p.is_expired(next_date))
+            .filter(|p| !self.is_cash_settled(&p.symbol()))
+            .cloned()
+            .collect();
+
+        for position in candidates {
+            let quote = match self.quote_for(position.name()) {
+                Some(q) => q,
+                None => continue,
+            };
+
+            if in_the_money_by(&quote) <= Money::zero() {
+                continue;
+            }
+
+            let dividend = self.dividend_before(&position.symbol(), next_date);
+
+            let assigned = match self.early_assignment {
+                Some(ref model) => model.should_assign(&quote, dividend),
+                None => false,
+            };
+
+            if assigned {
+                let (shares, cash) = self.settle_by_delivery(&position, &quote);
+                let kind = ExerciseKind::EarlyAssigned;
+                self.record_exercise_event(&position, &quote, kind, shares, cash);
+            }
+        }
+    }
+
+    fn close_all_open_positions(&mut self) {
+        let mut orders = vec![];
+
+        for position in self.open_positions() {
+            let quote = self.mark_for(position);
+
+            // close at the worst possible price
+            let price = if position.is_long() {
+                quote.bid()
+            } else {
+                quote.ask()
+            };
+
+            orders.push((closing_order(position, &quote, price), quote));
+        }
+
+        for (o, q) in orders {
+            self.fill_order(o, &q, None);
+        }
+
+        // and sell (or buy back) any shares left over from exercise/assignment
+        let stock: Vec<(Rc<str>, i32)> = self.open_stock_positions()
+            .iter()
+            .map(|s| (s.symbol(), s.quantity()))
+            .collect();
+
+        for (symbol, quantity) in stock {
+            let price = self.underlying_price_for(&symbol);
+            self.apply_stock_trade(symbol, -quantity, price, JournalEntryKind::StockTrade);
+        }
+    }
+}
+
+// an order for all of the position's contracts, in the position's trade group
+fn closing_order(position: &Position, quote: &Quote, price: Money) -> Order {
+    let quantity = position.quantity().abs();
+
+    let order = if position.is_long() {
+        Order::new_sell_close_order(quote, quantity, price)
+    } else {
+        Order::new_buy_close_order(quote, quantity, price)
+    };
+
+    match position.group() {
+        Some(group) => order.with_group(group),
+        None => order,
+    }
+}
+
+fn absolute(amount: Money) -> Money {
+    if amount < Money::zero() {
+        Money::zero() - amount
+    } else {
+        amount
+    }
+}
+
+// to the cent, so rejection reasons are readable
+fn rounded(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// how far the underlying is past the strike (negative when out of the money)
+fn in_the_money_by(quote: &Quote) -> Money {
+    if quote.is_call() {
+        quote.underlying_price() - quote.strike_price()
+    } else {
+        quote.strike_price() - quote.underlying_price()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::assignment::extrinsic_value::ExtrinsicValueAssignment;
+    use assassin::commission::charles_schwab::CharlesSchwab;
+    use assassin::conditional_order::{ConditionalOrder, Trigger};
+    use assassin::exercise::ExerciseKind;
+    use assassin::margin::portfolio::PortfolioMargin;
+    use assassin::quote_builder::QuoteBuilder;
+
+    struct VecFeed {
+        quotes: Vec<Quote>,
+    }
+
+    impl DataFeed for VecFeed {
+        fn next_quote(&mut self) -> Option<Quote> {
+            if self.quotes.is_empty() {
+                None
+            } else {
+                Some(self.quotes.remove(0))
+            }
+        }
+    }
+
+    fn dummy_quote(day: u32, call: bool, strike: i32, bid: Money, ask: Money) -> Quote {
+        QuoteBuilder::new()
+            .quoted_on(2013, 1, day)
+            .with_call(call)
+            .with_strike(strike)
+            .with_bid_ask(bid, ask)
+            .build()
+    }
+
+    fn expiring_quote(day: u32, call: bool, strike: i32) -> Quote {
+        QuoteBuilder::new()
+            .expiring(2013, 1, 3)
+            .quoted_on(2013, 1, day)
+            .with_call(call)
+            .with_strike(strike)
+            .build()
+    }
+
+    pub fn day_of_quotes(day: u32) -> Vec<Quote> {
+        vec![
+            dummy_quote(day, true, 95, Money::new(7, 0), Money::new(7, 20)),
+            dummy_quote(day, true, 105, Money::new(2, 0), Money::new(2, 10)),
+        ]
+    }
+
+    pub fn broker_with(quotes: Vec<Quote>) -> Broker {
+        let mut broker = Broker::new(
+            Money::new(100_000, 0),
+            Box::new(CharlesSchwab::new()),
+            Box::new(VecFeed { quotes: quotes }),
+        );
+
+        assert!(broker.process_simulation_data());
+
+        broker
+    }
+
+    pub fn broker() -> Broker {
+        let mut quotes = day_of_quotes(2);
+        quotes.extend(day_of_quotes(3));
+
+        broker_with(quotes)
+    }
+
+    fn vertical(broker: &Broker) -> ComboOrder {
+        let quotes = broker.call_quotes_for("AAPL");
+
+        ComboOrder::new(2)
+            .buy_to_open(quotes[0], 1)
+            .sell_to_open(quotes[1], 1)
+    }
+
+    #[test]
+    fn test_margin_usage_under_each_calculator() {
+        let mut quotes = day_of_quotes(2);
+        quotes.extend(day_of_quotes(3));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+        broker.add_comparison_margin_calculator(Box::new(PortfolioMargin::new()));
+
+        let quote = broker.call_quotes_for("AAPL")[1].clone();
+        broker.process_order(Order::new_sell_open_order(&quote, 1, quote.bid()));
+
+        assert!(broker.process_simulation_data());
+
+        let usage = broker.margin_usage();
+        assert!(usage[0].is_enforced() && !usage[1].is_enforced());
+
+        // 20% of the underlying less $5 out of the money under Reg-T, the
+        // $10 a 15% rally would cost under portfolio margin
+        assert!(usage[0].highest_requirement() == Money::new(1_500, 0));
+        assert!(usage[1].highest_requirement() == Money::new(1_000, 0));
+
+        let difference = usage[1].lowest_buying_power() - usage[0].lowest_buying_power();
+        assert!(difference == Money::new(500, 0));
+    }
+
+    #[test]
+    fn test_combo_fills_all_legs() {
+        let mut broker = broker();
+
+        let combo = vertical(&broker).with_debit_limit(Money::new(5, 20));
+        broker.process_combo_order(combo);
+
+        assert_eq!(broker.open_positions().len(), 2);
+        assert_eq!(broker.combo_ids(), vec![1]);
+        assert_eq!(broker.positions_for_combo(1).len(), 2);
+
+        // one base fee for the ticket plus 4 contracts
+        let commish = Money::new(4, 95) + Money::new(0, 65) * 4;
+        assert!(broker.commission_paid() == commish);
+
+        let net_debit = Money::new(5, 5) * 100 * 2;
+        assert!(broker.account_balance() == Money::new(100_000, 0) - net_debit - commish);
+    }
+
+    #[test]
+    fn test_combo_outside_limit_fills_nothing() {
+        let mut broker = broker();
+
+        // the $5.05 midpoint is inside the limit but the $5.20 net ask isn't
+        let combo = vertical(&broker).with_debit_limit(Money::new(5, 5));
+        broker.process_combo_order(combo);
+
+        assert!(broker.positions().is_empty());
+        assert!(broker.combo_ids().is_empty());
+        assert!(broker.account_balance() == Money::new(100_000, 0));
+    }
+
+    #[test]
+    fn test_combo_commission_skips_exempt_legs() {
+        let mut quotes = day_of_quotes(2);
+        quotes.push(dummy_quote(3, true, 95, Money::new(7, 0), Money::new(7, 20)));
+        quotes.push(dummy_quote(3, true, 105, Money::new(0, 4), Money::new(0, 6)));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+        broker.process_combo_order(vertical(&broker).with_debit_limit(Money::new(5, 20)));
+
+        assert!(broker.process_simulation_data());
+
+        let quotes = broker.call_quotes_for("AAPL");
+        let close = ComboOrder::new(2)
+            .sell_to_close(quotes[0], 1)
+            .buy_to_close(quotes[1], 1);
+        broker.process_combo_order(close);
+
+        // buying the short back at $0.05 is free, so the $95 call pays it all
+        let fills = broker.filled_orders();
+        assert!(fills[2].commission() == Money::new(4, 95) + Money::new(0, 65) * 2);
+        assert!(fills[3].commission() == Money::zero());
+    }
+
+    #[test]
+    fn test_one_cancels_other() {
+        let mut quotes = day_of_quotes(2);
+        quotes.push(dummy_quote(3, true, 95, Money::new(7, 40), Money::new(7, 80)));
+        quotes.push(dummy_quote(3, true, 105, Money::new(2, 0), Money::new(2, 10)));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+
+        let sell = Order::new_sell_close_order(&quote, 1, quote.bid());
+        broker.process_order_request(OrderRequest::OneCancelsOther(vec![
+            ConditionalOrder::take_profit(sell.clone(), Money::new(7, 50)),
+            ConditionalOrder::stop_loss(sell, Money::new(6, 0)),
+        ]));
+
+        assert_eq!(broker.working_orders().len(), 2);
+
+        // the take profit fills before the model would see day 3
+        assert!(broker.process_simulation_data());
+
+        assert!(broker.open_positions().is_empty());
+        assert!(broker.working_orders().is_empty());
+
+        let position = broker.positions()[0];
+        assert_eq!(position.order_count(), 2);
+        assert!(position.orders()[1].fill_price() == Money::new(7, 60));
+    }
+
+    #[test]
+    fn test_rejected_oco_leg_keeps_its_sibling() {
+        let mut quotes = day_of_quotes(2);
+        quotes.push(dummy_quote(3, true, 95, Money::new(7, 40), Money::new(7, 80)));
+        quotes.push(dummy_quote(3, true, 105, Money::new(2, 0), Money::new(2, 10)));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+        broker.set_risk_limits(RiskLimits::new().with_max_contracts_per_order(2));
+
+        let quotes: Vec<Quote> = broker.call_quotes_for("AAPL").into_iter().cloned().collect();
+        broker.process_order_request(OrderRequest::OneCancelsOther(vec![
+            ConditionalOrder::new(
+                Order::new_buy_open_order(&quotes[0], 5, quotes[0].ask()),
+                Trigger::OptionPriceAtOrAbove(Money::new(7, 50)),
+            ),
+            ConditionalOrder::new(
+                Order::new_buy_open_order(&quotes[1], 1, quotes[1].ask()),
+                Trigger::OptionPriceAtOrAbove(Money::new(3, 0)),
+            ),
+        ]));
+
+        // the $95 call triggers but is too big to fill
+        assert!(broker.process_simulation_data());
+
+        assert!(broker.positions().is_empty());
+        assert_eq!(broker.rejected_orders().len(), 1);
+        assert_eq!(broker.working_orders().len(), 1);
+        assert_eq!(broker.working_orders()[0].order().option_name(), quotes[1].name());
+    }
+
+    #[test]
+    fn test_expiration_exercise_and_assignment() {
+        let mut quotes = vec![];
+
+        for day in 2..5 {
+            quotes.push(expiring_quote(day, true, 95));
+            quotes.push(expiring_quote(day, false, 105));
+            quotes.push(expiring_quote(day, false, 90));
+        }
+
+        let mut broker = broker_with(quotes);
+
+        for q in broker.quotes_for("AAPL").into_iter().cloned().collect::<Vec<Quote>>() {
+            let order = if q.is_put() && q.strike_price() == Money::new(105, 0) {
+                Order::new_sell_open_order(&q, 1, q.bid())
+            } else {
+                Order::new_buy_open_order(&q, 1, q.ask())
+            };
+
+            broker.process_order(order);
+        }
+
+        assert_eq!(broker.open_positions().len(), 3);
+
+        // day 3 is the last trading day, settlement happens before day 4
+        assert!(broker.process_simulation_data());
+
+        assert!(broker.open_positions().is_empty());
+
+        let mut kinds: Vec<ExerciseKind> = broker
+            .exercise_events()
+            .iter()
+            .map(|e| e.kind())
+            .collect();
+        kinds.sort_by_key(|k| *k as i32);
+
+        assert_eq!(
+            kinds,
+            vec![
+                ExerciseKind::Exercised,
+                ExerciseKind::Assigned,
+                ExerciseKind::ExpiredWorthless,
+            ]
+        );
+
+        // 100 shares from the long call @ $95 and 100 from the short put @ $105
+        let stock = broker.open_stock_positions();
+        assert_eq!(stock[0].quantity(), 200);
+        assert!(stock[0].cost_basis() == Money::new(20_000, 0));
+
+        for p in broker.positions() {
+            assert!(p.orders().last().unwrap().closed_by_broker());
+        }
+
+        // both share purchases were journaled at the strike
+        assert!(broker.journal_total(JournalEntryKind::Settlement) == Money::new(-20_000, 0));
+        assert!(broker.reconcile_journal().is_ok());
+
+        // cash that nothing else accounts for
+        broker.post(JournalEntryKind::Dividend, Money::new(5, 0), Rc::from("AAPL"));
+        assert!(broker.reconcile_journal().is_err());
+    }
+
+    #[test]
+    fn test_cash_settled_expiration() {
+        let mut quotes = vec![];
+
+        for day in 2..5 {
+            quotes.push(expiring_quote(day, true, 95));
+            quotes.push(expiring_quote(day, true, 105));
+        }
+
+        let mut broker = broker_with(quotes);
+        broker.set_cash_settled("AAPL");
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_buy_open_order(&quote, 2, quote.ask()));
+
+        let balance = broker.account_balance();
+
+        assert!(broker.process_simulation_data());
+
+        // $5.00 in the money, 2 contracts
+        assert!(broker.account_balance() == balance + Money::new(1_000, 0));
+        assert!(broker.open_stock_positions().is_empty());
+        assert_eq!(broker.exercise_events()[0].kind(), ExerciseKind::CashSettled);
+    }
+
+    #[test]
+    fn test_early_assignment_before_dividend() {
+        let mut quotes = day_of_quotes(2);
+        quotes.extend(day_of_quotes(3));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+        broker.set_early_assignment_model(Box::new(ExtrinsicValueAssignment::new(Money::zero())));
+        broker.add_corporate_action(CorporateAction::cash_dividend(
+            "AAPL",
+            Utc.ymd(2013, 1, 4).and_hms(0, 0, 0),
+            Money::new(2, 50),
+        ));
+
+        // $5.00 in the money with $2.10 of extrinsic value
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_sell_open_order(&quote, 1, quote.bid()));
+
+        assert!(broker.process_simulation_data());
+
+        assert!(broker.open_positions().is_empty());
+        assert_eq!(broker.open_stock_positions()[0].quantity(), -100);
+
+        let event = &broker.exercise_events()[0];
+        assert_eq!(event.kind(), ExerciseKind::EarlyAssigned);
+        assert!(event.cash() == Money::new(9_500, 0));
+
+        // not paid until the ex-date's quotes come in
+        assert!(broker.dividends_received() == Money::zero());
+        assert!(!broker.process_simulation_data());
+
+        // the short shares owe the dividend they were assigned ahead of
+        assert!(broker.dividends_received() == Money::new(-250, 0));
+    }
+
+    #[test]
+    fn test_trade_spanning_a_split() {
+        let quote = |day, strike, underlying| {
+            QuoteBuilder::new()
+                .quoted_on(2013, 1, day)
+                .with_strike(strike)
+                .with_underlying(Money::new(underlying, 0))
+                .build()
+        };
+
+        let mut quotes = vec![];
+
+        for &(day, underlying) in &[(2, 100), (3, 100), (4, 50), (7, 50), (8, 50)] {
+            quotes.push(quote(day, underlying, underlying));
+            quotes.push(quote(day, underlying + 5, underlying));
+        }
+
+        let mut broker = broker_with(quotes);
+        broker.add_corporate_action(CorporateAction::split(
+            "AAPL",
+            Utc.ymd(2013, 1, 4).and_hms(0, 0, 0),
+            2,
+            1,
+        ));
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_buy_open_order(&quote, 2, quote.ask()));
+
+        assert!(broker.process_simulation_data());
+        assert!(broker.process_simulation_data());
+        assert_eq!(broker.open_positions()[0].quantity(), 4);
+
+        // half of the post-split contracts
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_sell_close_order(&quote, 2, quote.bid()));
+        assert!(broker.trades().is_empty());
+
+        assert!(broker.process_simulation_data());
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_sell_close_order(&quote, 2, quote.bid()));
+        assert!(broker.open_positions().is_empty());
+
+        let trades = broker.trades();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].contracts(), 4);
+    }
+
+    #[test]
+    fn test_checks_use_the_adjusted_multiplier() {
+        let quote = |day, strike, underlying| {
+            QuoteBuilder::new()
+                .quoted_on(2013, 1, day)
+                .with_strike(strike)
+                .with_underlying(underlying)
+                .build()
+        };
+
+        let mut quotes = vec![];
+
+        for day in 2..4 {
+            quotes.push(quote(day, 90, Money::new(100, 0)));
+            quotes.push(quote(day, 105, Money::new(100, 0)));
+        }
+
+        for day in 4..6 {
+            quotes.push(quote(day, 60, Money::new(66, 67)));
+            quotes.push(quote(day, 70, Money::new(66, 67)));
+        }
+
+        let mut broker = broker_with(quotes);
+        broker.add_corporate_action(CorporateAction::split(
+            "AAPL",
+            Utc.ymd(2013, 1, 4).and_hms(0, 0, 0),
+            3,
+            2,
+        ));
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+
+        assert!(broker.process_simulation_data());
+        assert!(broker.process_simulation_data());
+        assert_eq!(broker.open_positions()[0].multiplier(), 150);
+
+        // $1.05 for 150 shares plus commission is over the limit, where 100
+        // shares wouldn't be
+        broker.set_risk_limits(RiskLimits::new().with_max_loss_per_trade(Money::new(150, 0)));
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        assert!(quote.name() == broker.open_positions()[0].name());
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+
+        let rejected = broker.rejected_orders();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(
+            rejected[0].reason(),
+            Some("risk limit: max loss of $163.10 is over the limit of $150.00")
+        );
+    }
+
+    #[test]
+    fn test_margin_uses_the_mark_method() {
+        let mut broker = broker();
+        broker.set_mark_method(MarkMethod::Mid);
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_buy_open_order(&quote, 2, quote.ask()));
+
+        // long options require their full value, at the same mark the
+        // account balance uses
+        let holdings = broker.holdings();
+        assert!(holdings[0].price() == quote.midpoint_price());
+        assert!(broker.margin_requirement().initial() == quote.midpoint_price() * 200);
+    }
+
+    #[test]
+    fn test_naked_calls_need_buying_power() {
+        let mut broker = broker();
+        let quote = broker.call_quotes_for("AAPL")[1].clone();
+
+        // $105 strike with the underlying at $100: $1,500 per contract
+        broker.process_order(Order::new_sell_open_order(&quote, 100, quote.bid()));
+
+        assert!(broker.positions().is_empty());
+        assert_eq!(broker.rejected_orders().len(), 1);
+
+        broker.process_order(Order::new_sell_open_order(&quote, 10, quote.bid()));
+
+        assert_eq!(broker.open_positions().len(), 1);
+        assert!(broker.margin_requirement().initial() == Money::new(15_000, 0));
+        assert!(
+            broker.buying_power() == broker.unrealized_account_balance() - Money::new(15_000, 0)
+        );
+    }
+
+    #[test]
+    fn test_risk_limits_reject_orders() {
+        let mut broker = broker();
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+
+        broker.set_risk_limits(
+            RiskLimits::new()
+                .with_max_contracts_per_order(5)
+                .with_max_contracts_per_position(8)
+                .with_max_loss_per_trade(Money::new(4_000, 0)),
+        );
+
+        broker.process_order(Order::new_buy_open_order(&quote, 6, quote.ask()));
+        broker.process_order(Order::new_buy_open_order(&quote, 5, quote.ask()));
+        broker.process_order(Order::new_buy_open_order(&quote, 5, quote.ask()));
+
+        assert_eq!(broker.open_positions()[0].quantity(), 5);
+
+        // closing is always allowed
+        broker.process_order(Order::new_sell_close_order(&quote, 5, quote.bid()));
+        assert!(broker.open_positions().is_empty());
+
+        broker.set_risk_limits(RiskLimits::new().with_max_loss_per_trade(Money::new(1_000, 0)));
+        broker.process_order(Order::new_buy_open_order(&quote, 2, quote.ask()));
+
+        let reasons: Vec<&str> = broker
+            .rejected_orders()
+            .iter()
+            .map(|o| o.reason().unwrap())
+            .collect();
+
+        assert_eq!(reasons.len(), 3);
+        assert!(reasons[0].starts_with("risk limit: contracts per order of 6"));
+        assert!(reasons[1].starts_with("risk limit: contracts of"));
+        assert!(reasons[2].starts_with("risk limit: max loss"));
+
+        // a naked call can lose any amount
+        let quote = broker.call_quotes_for("AAPL")[1].clone();
+        broker.process_order(Order::new_sell_open_order(&quote, 1, quote.bid()));
+
+        let rejected = broker.rejected_orders();
+        assert!(rejected[3].reason().unwrap().starts_with("risk limit: max loss is unlimited"));
+    }
+
+    #[test]
+    fn test_circuit_breaker_liquidates() {
+        let mut quotes = day_of_quotes(2);
+        quotes.extend(day_of_quotes(3));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+
+        broker.add_circuit_breaker(CircuitBreaker::margin_utilization(
+            0.5,
+            BreakerAction::Liquidate,
+        ));
+
+        // the premium is the initial requirement, about 0.7% of the account
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+        assert!(!broker.is_halted());
+
+        assert!(broker.process_simulation_data());
+
+        assert!(broker.is_halted());
+        assert!(broker.open_positions().is_empty());
+        assert_eq!(broker.circuit_breaker_events().len(), 1);
+        assert!(broker.circuit_breaker_events()[0].reason().starts_with("margin utilization"));
+
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+
+        let rejected = broker.rejected_orders();
+        assert_eq!(rejected.len(), 1);
+        assert!(rejected[0].reason().unwrap().starts_with("circuit breaker: "));
+    }
+
+    #[test]
+    fn test_order_history() {
+        let mut broker = broker();
+        let quotes: Vec<Quote> = broker.call_quotes_for("AAPL").into_iter().cloned().collect();
+
+        broker.process_order(Order::new_buy_open_order(&quotes[0], 1, quotes[0].ask()));
+        broker.process_order(Order::new_sell_open_order(&quotes[1], 100, quotes[1].bid()));
+        broker.process_combo_order(vertical(&broker).with_debit_limit(Money::new(5, 0)));
+
+        let stop = Order::new_sell_close_order(&quotes[0], 1, quotes[0].bid());
+        broker.process_order_request(OrderRequest::from(ConditionalOrder::stop_loss(
+            stop,
+            Money::new(6, 0),
+        )));
+
+        let statuses: Vec<OrderStatus> = broker
+            .submitted_orders()
+            .iter()
+            .map(|o| o.status())
+            .collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                OrderStatus::Filled,
+                OrderStatus::Rejected,
+                OrderStatus::Cancelled,
+                OrderStatus::Working,
+            ]
+        );
+
+        assert_eq!(broker.total_order_count(), 1);
+        assert_eq!(broker.filled_orders()[0].order_id(), Some(1));
+        assert!(broker.rejected_orders()[0].reason().is_some());
+
+        // the fill, the combo's first leg and the stop all trade the $95 call
+        let name = quotes[0].name();
+        assert_eq!(broker.submitted_orders_for_position(&name).len(), 3);
+        assert_eq!(broker.filled_orders_for_position(&name).len(), 1);
+    }
+
+    #[test]
+    fn test_trade_groups_track_legs_separately() {
+        let mut broker = broker();
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+
+        let first = TradeGroup::new("PMCC", 1);
+        let second = TradeGroup::new("PMCC", 2);
+
+        broker.process_combo_order(
+            vertical(&broker)
+                .with_debit_limit(Money::new(5, 20))
+                .with_group(first.clone()),
+        );
+        broker.process_order(
+            Order::new_buy_open_order(&quote, 1, quote.ask()).with_group(second.clone()),
+        );
+
+        // both groups hold the $95 call without adding up into one position
+        assert_eq!(broker.open_positions().len(), 3);
+        assert_eq!(broker.open_positions_in_group(&first).len(), 2);
+        assert_eq!(broker.open_positions_in_group(&second)[0].quantity(), 1);
+
+        broker.process_order(
+            Order::new_sell_close_order(&quote, 1, quote.bid()).with_group(second.clone()),
+        );
+
+        assert_eq!(broker.open_positions_in_group(&first)[0].quantity(), 2);
+        assert_eq!(broker.trade_groups("PMCC"), vec![first.clone(), second.clone()]);
+        assert_eq!(broker.open_trade_groups("PMCC"), vec![first]);
+        assert!(broker.trade_groups("other").is_empty());
+
+        // bought and sold at the midpoint, so only the commission is lost
+        let commish = broker.positions_in_group(&second)[0].commission_paid();
+        assert!(commish > Money::zero());
+        assert!(broker.trade_group_pnl(&second) == Money::zero() - commish);
+    }
+
+    #[test]
+    fn test_portfolio_greeks() {
+        let quote_on = |day: u32, strike: i32| {
+            QuoteBuilder::new()
+                .quoted_on(2013, 1, day)
+                .with_strike(strike)
+                .with_bid_ask(Money::new(7, 0), Money::new(7, 20))
+                .with_implied_volatility(0.3)
+                .with_greeks(0.5, 0.02, 0.25)
+                .build()
+        };
+
+        let mut broker = broker_with(vec![
+            quote_on(2, 95),
+            quote_on(2, 105),
+            quote_on(3, 95),
+            quote_on(3, 105),
+            quote_on(4, 95),
+            quote_on(4, 105),
+        ]);
+        broker.set_beta("AAPL", 1.5);
+
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_buy_open_order(&quote, 2, quote.ask()));
+
+        let greeks = broker.portfolio_greeks();
+
+        assert!((greeks.dollar_delta() - 10_000.0).abs() < 0.01);
+        assert!((greeks.beta_weighted_delta() - 15_000.0).abs() < 0.01);
+        assert!((greeks.vega() - 50.0).abs() < 0.01);
+        assert!(greeks.theta() < 0.0);
+        assert_eq!(greeks, broker.greeks_for_symbol("AAPL"));
+        assert_eq!(broker.greeks_for_symbol("MSFT"), Greeks::zero());
+
+        assert!(broker.process_simulation_data());
+
+        let &(date, ref recorded) = broker.greeks_history().last().unwrap();
+        assert_eq!(date, Utc.ymd(2013, 1, 3).and_hms(0, 0, 0));
+        // a day closer to expiration, so only theta has changed
+        assert_eq!(recorded.dollar_delta(), greeks.dollar_delta());
+        assert!(recorded.theta() < greeks.theta());
+    }
+
+    #[test]
+    fn test_daily_pnl() {
+        let mut quotes = day_of_quotes(2);
+        quotes.extend(day_of_quotes(3));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+        let commish = broker.commission_paid();
+
+        assert!(broker.process_simulation_data());
+
+        // bought at the $7.10 midpoint and marked at the $7.00 bid
+        let day_3 = broker.account_pnl_history().last().unwrap().clone();
+        assert!(day_3.unrealized() == Money::new(-10, 0));
+        assert!(day_3.realized() == Money::zero() - commish);
+
+        let position = broker.positions()[0].clone();
+        let history = broker.position_pnl_history(&position).unwrap();
+        assert!(history[0].total() == day_3.total());
+
+        assert!(!broker.process_simulation_data());
+
+        let days = broker.daily_account_pnl();
+        assert_eq!(days.len(), 3);
+
+        let total: Money = days.iter().map(|&(_, pnl)| pnl).sum();
+        assert!(total == broker.account_balance() - broker.opening_balance());
+    }
+
+    #[test]
+    fn test_equity_curve() {
+        let mut quotes = day_of_quotes(2);
+        quotes.extend(day_of_quotes(3));
+        quotes.extend(day_of_quotes(4));
+
+        let mut broker = broker_with(quotes);
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+        let cash = broker.account_balance();
+
+        assert!(broker.process_simulation_data());
+        assert!(!broker.process_simulation_data());
+
+        let curve = broker.equity_curve();
+        assert_eq!(curve.len(), 3);
+
+        // marked at the $7.00 bid
+        assert!(curve[1].cash() == cash);
+        assert!(curve[1].market_value() == Money::new(700, 0));
+        assert!(curve[1].margin_used() > Money::zero());
+        assert_eq!(curve[1].open_positions(), 1);
+
+        // everything was closed out at the end
+        assert_eq!(curve[2].open_positions(), 0);
+        assert!(curve[2].equity() == broker.account_balance());
+
+        // the underlying's close on each of the same days
+        let closes = broker.underlying_closes("AAPL").unwrap();
+        assert_eq!(closes.len(), 3);
+        assert!(closes[2].0 == curve[2].date());
+        assert_eq!(broker.underlying_symbols().len(), 1);
+        assert!(broker.lowest_unrealized_account_balance() < broker.opening_balance());
+    }
+
+    #[test]
+    fn test_trades() {
+        let mut quotes = day_of_quotes(2);
+        quotes.extend(day_of_quotes(3));
+        quotes.extend(day_of_quotes(4));
+        quotes.extend(day_of_quotes(7));
+
+        let mut broker = broker_with(quotes);
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+        assert!(broker.process_simulation_data());
+
+        // closed by the model at the $7.10 it was bought for
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_sell_close_order(&quote, 1, quote.bid()));
+        assert!(broker.process_simulation_data());
+
+        // and opened again, to be closed by the broker at the end
+        let quote = broker.call_quotes_for("AAPL")[0].clone();
+        broker.process_order(Order::new_buy_open_order(&quote, 1, quote.ask()));
+        assert!(!broker.process_simulation_data());
+
+        let trades = broker.trades();
+        assert_eq!(trades.len(), 2);
+
+        let first = &trades[0];
+        assert!(first.profit() == Money::zero() - first.commission());
+        assert!(first.commission() > Money::zero());
+        assert_eq!(first.days_held(), 1);
+        assert!(first.is_long());
+        assert!(!first.closed_by_broker());
+
+        // marked at the $7.00 bid while it was open
+        assert!(first.max_adverse_excursion() < Money::zero());
+        assert!(first.max_favorable_excursion() == Money::zero());
+
+        assert!(trades[1].closed_by_broker());
+        assert!(trades[1].opened() >= first.closed());
+
+        let total: Money = trades.iter().map(|t| t.profit()).sum();
+        let position = broker.positions()[0];
+        assert!(total == position.realized_profit() - position.commission_paid());
+    }
+
+    #[test]
+    fn test_interest_on_cash() {
+        use assassin::interest::constant::ConstantRate;
+
+        let mut quotes = day_of_quotes(3);
+        quotes.extend(day_of_quotes(4));
+        quotes.extend(day_of_quotes(7));
+        quotes.extend(day_of_quotes(8));
+
+        let mut broker = broker_with(quotes);
+        broker.set_credit_interest_rate(Box::new(ConstantRate::new(0.036)));
+
+        // friday to monday earns 3 days: $100,000 * 3.6% * 3 / 360
+        assert!(broker.process_simulation_data());
+
+        assert!(broker.interest_earned() == Money::new(30, 0));
+        assert!(broker.account_balance() == Money::new(100_030, 0));
+        assert_eq!(broker.interest_accruals()[0].days(), 3);
+    }
+}
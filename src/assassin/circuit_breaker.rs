@@ -0,0 +1,162 @@
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BreakerAction {
+    // orders that open (or add to) positions are rejected from then on
+    StopOpening,
+    // everything is closed out and nothing can be opened from then on
+    Liquidate,
+}
+
+impl BreakerAction {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            BreakerAction::StopOpening => "stop opening",
+            BreakerAction::Liquidate => "liquidate",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Trigger {
+    // percent the unrealized balance is below its peak
+    Drawdown(f64),
+    // lost since the close of the previous trading day
+    DailyLoss(Money),
+    // the initial margin requirement as a percent of the unrealized balance
+    MarginUtilization(f64),
+}
+
+// Checked by the broker at the end of every trading day.  Once one trips it
+// stays tripped for the rest of the simulation.
+#[derive(Clone, Copy)]
+pub struct CircuitBreaker {
+    trigger: Trigger,
+    action: BreakerAction,
+}
+
+impl CircuitBreaker {
+    #[allow(dead_code)]
+    pub fn drawdown(percent: f64, action: BreakerAction) -> CircuitBreaker {
+        if percent <= 0.0 || percent > 100.0 {
+            panic!("percent must be > 0.0 and <= 100.0 (got {})", percent);
+        }
+
+        CircuitBreaker {
+            trigger: Trigger::Drawdown(percent),
+            action: action,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn daily_loss(loss: Money, action: BreakerAction) -> CircuitBreaker {
+        if loss <= Money::zero() {
+            panic!("loss must be > 0.0 (got {})", loss);
+        }
+
+        CircuitBreaker {
+            trigger: Trigger::DailyLoss(loss),
+            action: action,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn margin_utilization(percent: f64, action: BreakerAction) -> CircuitBreaker {
+        if percent <= 0.0 {
+            panic!("percent must be > 0.0 (got {})", percent);
+        }
+
+        CircuitBreaker {
+            trigger: Trigger::MarginUtilization(percent),
+            action: action,
+        }
+    }
+
+    pub fn action(&self) -> BreakerAction {
+        self.action
+    }
+
+    // why the breaker trips, if it does
+    pub fn check(
+        &self,
+        drawdown: f64,
+        daily_change: Money,
+        margin_utilization: f64,
+    ) -> Option<String> {
+        match self.trigger {
+            Trigger::Drawdown(percent) if drawdown >= percent => Some(format!(
+                "balance is {:.2}% below its peak (limit {:.2}%)",
+                drawdown,
+                percent,
+            )),
+            Trigger::DailyLoss(loss) if Money::zero() - daily_change >= loss => Some(format!(
+                "lost {} today (limit {})",
+                Money::zero() - daily_change,
+                loss,
+            )),
+            Trigger::MarginUtilization(percent) if margin_utilization >= percent => Some(format!(
+                "margin utilization is {:.2}% (limit {:.2}%)",
+                margin_utilization,
+                percent,
+            )),
+            _ => None,
+        }
+    }
+}
+
+// When a CircuitBreaker tripped and what was done about it.
+#[derive(Clone)]
+pub struct CircuitBreakerEvent {
+    date: DateTime<Utc>,
+    action: BreakerAction,
+    reason: String,
+}
+
+impl CircuitBreakerEvent {
+    pub fn new(date: DateTime<Utc>, action: BreakerAction, reason: String) -> CircuitBreakerEvent {
+        CircuitBreakerEvent {
+            date: date,
+            action: action,
+            reason: reason,
+        }
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    pub fn action(&self) -> BreakerAction {
+        self.action
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let drawdown = CircuitBreaker::drawdown(20.0, BreakerAction::Liquidate);
+        let daily_loss =
+            CircuitBreaker::daily_loss(Money::new(5_000, 0), BreakerAction::StopOpening);
+
+        assert!(drawdown.check(19.9, Money::zero(), 0.0).is_none());
+        assert!(drawdown.check(20.0, Money::zero(), 0.0).is_some());
+
+        assert!(daily_loss.check(0.0, Money::new(-4_999, 0), 0.0).is_none());
+        assert_eq!(
+            daily_loss.check(0.0, Money::new(-6_000, 0), 0.0),
+            Some("lost $6,000.00 today (limit $5,000.00)".to_string())
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use std::rc::Rc;
+
+use assassin::order::Order;
+use assassin::quote::Quote;
+use assassin::trade_group::TradeGroup;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+#[derive(Clone)]
+pub struct ComboLeg {
+    order: Order,
+    ratio: i32,
+}
+
+impl ComboLeg {
+    pub fn order(&self) -> &Order {
+        &self.order
+    }
+
+    #[allow(dead_code)]
+    pub fn ratio(&self) -> i32 {
+        self.ratio
+    }
+
+    pub fn option_name(&self) -> Rc<str> {
+        self.order.option_name()
+    }
+
+    // the leg's contribution to the net price of one unit of the combo.
+    // buying is a debit (positive) and selling is a credit (negative).
+    fn net_price(&self, price: Money) -> Money {
+        if self.order.is_buy() {
+            price * self.ratio
+        } else {
+            Money::zero() - price * self.ratio
+        }
+    }
+}
+
+// A multi-leg order (verticals, calendars, diagonals, condors, etc.) that is
+// filled atomically: either every leg fills or none of them do.
+//
+// Prices are quoted per unit of the combo, i.e., per `ratio` contracts of
+// each leg.  A positive price is a net debit and a negative price is a net
+// credit.
+#[derive(Clone)]
+pub struct ComboOrder {
+    legs: Vec<ComboLeg>,
+    quantity: i32,
+    limit: Option<Money>,
+    group: Option<TradeGroup>,
+}
+
+impl ComboOrder {
+    pub fn new(quantity: i32) -> ComboOrder {
+        if quantity <= 0 {
+            panic!("quantity must be > 0 (got {})", quantity);
+        }
+
+        ComboOrder {
+            legs: vec![],
+            quantity: quantity,
+            limit: None,
+            group: None,
+        }
+    }
+
+    // pay at most `limit` per unit of the combo
+    pub fn with_debit_limit(mut self, limit: Money) -> ComboOrder {
+        if limit < Money::zero() {
+            panic!("debit limit must be >= 0.0 (got {})", limit);
+        }
+
+        self.limit = Some(limit);
+        self
+    }
+
+    // receive at least `limit` per unit of the combo
+    #[allow(dead_code)]
+    pub fn with_credit_limit(mut self, limit: Money) -> ComboOrder {
+        if limit < Money::zero() {
+            panic!("credit limit must be >= 0.0 (got {})", limit);
+        }
+
+        self.limit = Some(Money::zero() - limit);
+        self
+    }
+
+    // tags every leg, including ones added afterwards
+    pub fn with_group(mut self, group: TradeGroup) -> ComboOrder {
+        for leg in self.legs.iter_mut() {
+            leg.order = leg.order.clone().with_group(group.clone());
+        }
+
+        self.group = Some(group);
+        self
+    }
+
+    pub fn buy_to_open(self, quote: &Quote, ratio: i32) -> ComboOrder {
+        let order = Order::new_buy_open_order(quote, ratio * self.quantity, quote.midpoint_price());
+        self.add_leg(order, ratio)
+    }
+
+    pub fn sell_to_open(self, quote: &Quote, ratio: i32) -> ComboOrder {
+        let order =
+            Order::new_sell_open_order(quote, ratio * self.quantity, quote.midpoint_price());
+        self.add_leg(order, ratio)
+    }
+
+    #[allow(dead_code)]
+    pub fn buy_to_close(self, quote: &Quote, ratio: i32) -> ComboOrder {
+        let order =
+            Order::new_buy_close_order(quote, ratio * self.quantity, quote.midpoint_price());
+        self.add_leg(order, ratio)
+    }
+
+    #[allow(dead_code)]
+    pub fn sell_to_close(self, quote: &Quote, ratio: i32) -> ComboOrder {
+        let order =
+            Order::new_sell_close_order(quote, ratio * self.quantity, quote.midpoint_price());
+        self.add_leg(order, ratio)
+    }
+
+    fn add_leg(mut self, order: Order, ratio: i32) -> ComboOrder {
+        if ratio <= 0 {
+            panic!("ratio must be > 0 (got {})", ratio);
+        }
+
+        if self.legs.iter().any(|l| l.option_name() == order.option_name()) {
+            panic!("combo already has a leg for {}", order.option_name());
+        }
+
+        let order = match self.group {
+            Some(ref group) => order.with_group(group.clone()),
+            None => order,
+        };
+
+        self.legs.push(ComboLeg {
+            order: order,
+            ratio: ratio,
+        });
+        self
+    }
+
+    pub fn legs(&self) -> &Vec<ComboLeg> {
+        &self.legs
+    }
+
+    #[allow(dead_code)]
+    pub fn quantity(&self) -> i32 {
+        self.quantity
+    }
+
+    // positive for a debit, negative for a credit, None to fill at the midpoint
+    #[allow(dead_code)]
+    pub fn limit(&self) -> Option<Money> {
+        self.limit
+    }
+
+    // NOTE: quotes must be in the same order as legs()
+
+    // the net debit paid at the natural market (buy legs at the ask, sell
+    // legs at the bid).  negative for a credit.
+    pub fn net_ask(&self, quotes: &[Quote]) -> Money {
+        self.net_price(quotes, |leg, q| if leg.order.is_buy() { q.ask() } else { q.bid() })
+    }
+
+    // the net credit received at the natural market, i.e., the same fills as
+    // net_ask() seen from the selling side.  negative for a debit.
+    pub fn net_bid(&self, quotes: &[Quote]) -> Money {
+        Money::zero() - self.net_ask(quotes)
+    }
+
+    // the sum of the legs' midpoints.  legs are filled at their own midpoints,
+    // so this is exactly what the combo fills at.
+    pub fn net_midpoint_price(&self, quotes: &[Quote]) -> Money {
+        self.net_price(quotes, |_, q| q.midpoint_price())
+    }
+
+    fn net_price<F>(&self, quotes: &[Quote], price_for: F) -> Money
+    where
+        F: Fn(&ComboLeg, &Quote) -> Money,
+    {
+        if quotes.len() != self.legs.len() {
+            panic!(
+                "expected {} quotes for combo (got {})",
+                self.legs.len(),
+                quotes.len()
+            );
+        }
+
+        self.legs
+            .iter()
+            .zip(quotes.iter())
+            .map(|(leg, q)| leg.net_price(price_for(leg, q)))
+            .sum()
+    }
+
+    // a debit limit has to cover the net ask and a credit limit can't ask for
+    // more than the net bid, so the combo would trade at the natural market
+    pub fn is_marketable(&self, quotes: &[Quote]) -> bool {
+        match self.limit {
+            Some(limit) if limit < Money::zero() => self.net_bid(quotes) >= Money::zero() - limit,
+            Some(limit) => self.net_ask(quotes) <= limit,
+            None => true,
+        }
+    }
+
+    // "AAPL: COMBO 5x [BUY 1 AAPL20130621C0045000 / SELL 1 AAPL20130215C0052000] at LIMIT $55.00"
+    pub fn summary(&self) -> String {
+        let legs: Vec<String> = self.legs
+            .iter()
+            .map(|l| {
+                format!(
+                    "{} {} {}",
+                    l.order.buy_or_sell_string(),
+                    l.ratio,
+                    l.option_name()
+                )
+            })
+            .collect();
+
+        let limit = match self.limit {
+            Some(l) if l < Money::zero() => format!("CREDIT {}", Money::zero() - l),
+            Some(l) => format!("DEBIT {}", l),
+            None => "MIDPOINT".to_string(),
+        };
+
+        format!("COMBO {}x [{}] at {}", self.quantity, legs.join(" / "), limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::quote_builder::QuoteBuilder;
+
+    fn quote(call: bool, strike: i32, bid: Money, ask: Money) -> Quote {
+        QuoteBuilder::new()
+            .with_call(call)
+            .with_strike(strike)
+            .with_bid_ask(bid, ask)
+            .build()
+    }
+
+    fn vertical() -> (ComboOrder, Vec<Quote>) {
+        let long = quote(true, 95, Money::new(7, 0), Money::new(7, 20));
+        let short = quote(true, 105, Money::new(2, 0), Money::new(2, 10));
+
+        let combo = ComboOrder::new(3)
+            .buy_to_open(&long, 1)
+            .sell_to_open(&short, 1);
+
+        (combo, vec![long, short])
+    }
+
+    #[test]
+    fn test_net_prices() {
+        let (combo, quotes) = vertical();
+
+        assert!(combo.net_ask(&quotes) == Money::new(5, 20));
+        assert!(combo.net_bid(&quotes) == Money::new(-5, 20));
+        assert!(combo.net_midpoint_price(&quotes) == Money::new(5, 5));
+        assert_eq!(combo.legs()[0].order().quantity(), 3);
+    }
+
+    #[test]
+    fn test_limits() {
+        let (combo, quotes) = vertical();
+
+        assert!(combo.is_marketable(&quotes));
+        // the $5.05 midpoint doesn't trade, the $5.20 net ask does
+        assert!(!combo.clone().with_debit_limit(Money::new(5, 5)).is_marketable(&quotes));
+        assert!(combo.with_debit_limit(Money::new(5, 20)).is_marketable(&quotes));
+
+        let put = quote(false, 95, Money::new(1, 0), Money::new(1, 20));
+        let credit = ComboOrder::new(1).sell_to_open(&put, 2);
+        let quotes = vec![put];
+
+        assert!(credit.net_midpoint_price(&quotes) == Money::new(-2, 20));
+        assert!(credit.net_bid(&quotes) == Money::new(2, 0));
+        assert!(credit.clone().with_credit_limit(Money::new(2, 0)).is_marketable(&quotes));
+        assert!(!credit.with_credit_limit(Money::new(2, 20)).is_marketable(&quotes));
+    }
+}
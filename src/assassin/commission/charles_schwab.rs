@@ -16,15 +16,34 @@ impl CharlesSchwab {
             per_contract: Money::new(0, 65),
         }
     }
+
+    fn is_exempt(&self, filled_order: &FilledOrder) -> bool {
+        // no commission on buy-to-close for <= $0.05
+        filled_order.buy_to_close() && filled_order.fill_price() <= Money::new(0, 5)
+    }
 }
 
 // https://www.schwab.com/public/schwab/active_trader/pricing
 impl Commission for CharlesSchwab {
     fn commission_for(&self, filled_order: &FilledOrder) -> Money {
-        if filled_order.buy_to_close() && filled_order.fill_price() <= Money::new(0, 5) {
-            Money::zero() // no commission on buy-to-close for <= $0.05
+        if self.is_exempt(filled_order) {
+            Money::zero()
         } else {
             self.base_fee + self.per_contract * filled_order.quantity()
         }
     }
+
+    // multi-leg orders pay the base fee once for the whole ticket
+    fn commission_for_combo(&self, legs: &[FilledOrder]) -> Money {
+        let contracts: i32 = legs.iter()
+            .filter(|l| !self.is_exempt(l))
+            .map(|l| l.quantity())
+            .sum();
+
+        if contracts == 0 {
+            Money::zero()
+        } else {
+            self.base_fee + self.per_contract * contracts
+        }
+    }
 }
@@ -0,0 +1,208 @@
+use std::rc::Rc;
+
+use assassin::order::Order;
+use assassin::quote::Quote;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// What has to happen before a ConditionalOrder is sent to be filled.  Option
+// prices are compared against the quote's midpoint since that's what orders
+// are filled at.
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub enum Trigger {
+    OptionPriceAtOrAbove(Money),
+    OptionPriceAtOrBelow(Money),
+    UnderlyingAtOrAbove(Money),
+    UnderlyingAtOrBelow(Money),
+}
+
+impl Trigger {
+    pub fn is_hit(&self, quote: &Quote) -> bool {
+        match *self {
+            Trigger::OptionPriceAtOrAbove(p) => quote.midpoint_price() >= p,
+            Trigger::OptionPriceAtOrBelow(p) => quote.midpoint_price() <= p,
+            Trigger::UnderlyingAtOrAbove(p) => quote.underlying_price() >= p,
+            Trigger::UnderlyingAtOrBelow(p) => quote.underlying_price() <= p,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        match *self {
+            Trigger::OptionPriceAtOrAbove(p) => format!("option >= {}", p),
+            Trigger::OptionPriceAtOrBelow(p) => format!("option <= {}", p),
+            Trigger::UnderlyingAtOrAbove(p) => format!("underlying >= {}", p),
+            Trigger::UnderlyingAtOrBelow(p) => format!("underlying <= {}", p),
+        }
+    }
+}
+
+// An Order that the Broker holds on to and evaluates at the start of every
+// simulated day, before the Model runs.  Once the trigger is hit the order
+// is filled like any other (stop), or only if the midpoint is at or better
+// than the limit (stop-limit).  A triggered stop-limit keeps working until
+// it can be filled.
+#[derive(Clone)]
+pub struct ConditionalOrder {
+    order: Order,
+    trigger: Trigger,
+    limit: Option<Money>,
+    triggered: bool,
+}
+
+impl ConditionalOrder {
+    #[allow(dead_code)]
+    pub fn new(order: Order, trigger: Trigger) -> ConditionalOrder {
+        ConditionalOrder {
+            order: order,
+            trigger: trigger,
+            limit: None,
+            triggered: false,
+        }
+    }
+
+    // closes a position once the option moves against it, i.e., a sell
+    // triggers when the price falls to `price` and a buy when it rises to it
+    #[allow(dead_code)]
+    pub fn stop_loss(order: Order, price: Money) -> ConditionalOrder {
+        let trigger = if order.is_sell() {
+            Trigger::OptionPriceAtOrBelow(price)
+        } else {
+            Trigger::OptionPriceAtOrAbove(price)
+        };
+
+        ConditionalOrder::new(order, trigger)
+    }
+
+    // closes a position once the option moves in its favor
+    #[allow(dead_code)]
+    pub fn take_profit(order: Order, price: Money) -> ConditionalOrder {
+        let trigger = if order.is_sell() {
+            Trigger::OptionPriceAtOrAbove(price)
+        } else {
+            Trigger::OptionPriceAtOrBelow(price)
+        };
+
+        ConditionalOrder::new(order, trigger)
+    }
+
+    // turns a stop into a stop-limit
+    #[allow(dead_code)]
+    pub fn with_limit(mut self, limit: Money) -> ConditionalOrder {
+        if limit < Money::zero() {
+            panic!("limit must be >= 0.0 (got {})", limit);
+        }
+
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn order(&self) -> &Order {
+        &self.order
+    }
+
+    pub fn option_name(&self) -> Rc<str> {
+        self.order.option_name()
+    }
+
+    #[allow(dead_code)]
+    pub fn trigger(&self) -> Trigger {
+        self.trigger
+    }
+
+    #[allow(dead_code)]
+    pub fn limit(&self) -> Option<Money> {
+        self.limit
+    }
+
+    #[allow(dead_code)]
+    pub fn is_triggered(&self) -> bool {
+        self.triggered
+    }
+
+    // checks the trigger against today's quote.  returns true if the order
+    // should be filled now.
+    pub fn evaluate(&mut self, quote: &Quote) -> bool {
+        if !self.triggered && self.trigger.is_hit(quote) {
+            self.triggered = true;
+        }
+
+        self.triggered && self.is_within_limit(quote.midpoint_price())
+    }
+
+    fn is_within_limit(&self, price: Money) -> bool {
+        match self.limit {
+            Some(limit) if self.order.is_buy() => price <= limit,
+            Some(limit) => price >= limit,
+            None => true,
+        }
+    }
+
+    // "BUY 5 AAPL20130215C0052000 when option >= $3.00 (LIMIT $3.20)"
+    pub fn summary(&self) -> String {
+        let limit = match self.limit {
+            Some(l) => format!(" (LIMIT {})", l),
+            None => "".to_string(),
+        };
+
+        format!(
+            "{} {} {} when {}{}",
+            self.order.buy_or_sell_string(),
+            self.order.quantity(),
+            self.option_name(),
+            self.trigger.summary(),
+            limit,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::quote_builder::QuoteBuilder;
+
+    fn call_quote(bid: Money, ask: Money, underlying: Money) -> Quote {
+        QuoteBuilder::new()
+            .with_bid_ask(bid, ask)
+            .with_underlying(underlying)
+            .build()
+    }
+
+    #[test]
+    fn test_stop_loss_direction() {
+        let quote = call_quote(Money::new(2, 0), Money::new(2, 20), Money::new(100, 0));
+
+        // long position: sell if the option drops to $2.00
+        let sell = Order::new_sell_close_order(&quote, 1, Money::zero());
+        let mut stop = ConditionalOrder::stop_loss(sell, Money::new(2, 0));
+        assert!(!stop.evaluate(&quote));
+
+        let lower = call_quote(Money::new(1, 90), Money::new(2, 0), Money::new(99, 0));
+        assert!(stop.evaluate(&lower));
+
+        // short position: buy back if the option rises to $2.00
+        let buy = Order::new_buy_close_order(&quote, 1, Money::zero());
+        let mut stop = ConditionalOrder::stop_loss(buy, Money::new(2, 0));
+        assert!(stop.evaluate(&quote));
+    }
+
+    #[test]
+    fn test_stop_limit_keeps_working() {
+        let quote = call_quote(Money::new(1, 0), Money::new(1, 20), Money::new(95, 0));
+        let sell = Order::new_sell_close_order(&quote, 1, Money::zero());
+
+        let mut order = ConditionalOrder::new(sell, Trigger::UnderlyingAtOrBelow(Money::new(96, 0)))
+            .with_limit(Money::new(1, 50));
+
+        // triggered, but the midpoint is below the limit
+        assert!(!order.evaluate(&quote));
+        assert!(order.is_triggered());
+
+        // trigger is no longer hit but the order stays armed
+        let recovered = call_quote(Money::new(1, 50), Money::new(1, 60), Money::new(97, 0));
+        assert!(order.evaluate(&recovered));
+    }
+}
@@ -0,0 +1,136 @@
+use std::rc::Rc;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CorporateActionKind {
+    // a regular per-share cash dividend.  options aren't adjusted for these.
+    CashDividend,
+    // a one-off per-share cash distribution.  option strikes are reduced by it.
+    SpecialDividend,
+    // `to` new shares for every `from` old ones (7:1 is to: 7, from: 1 and a
+    // 1:10 reverse split is to: 1, from: 10)
+    Split { to: i32, from: i32 },
+}
+
+// Something an underlying's issuer does that changes what its shares and
+// options are worth without the market moving.  Applied before the first
+// session on or after the ex-date.
+#[derive(Clone)]
+pub struct CorporateAction {
+    symbol: Rc<str>,
+    ex_date: DateTime<Utc>,
+    kind: CorporateActionKind,
+    // per-share cash for dividends, zero for splits
+    amount: Money,
+}
+
+impl CorporateAction {
+    pub fn cash_dividend(symbol: &str, ex_date: DateTime<Utc>, amount: Money) -> CorporateAction {
+        CorporateAction::dividend(symbol, ex_date, CorporateActionKind::CashDividend, amount)
+    }
+
+    pub fn special_dividend(
+        symbol: &str,
+        ex_date: DateTime<Utc>,
+        amount: Money,
+    ) -> CorporateAction {
+        CorporateAction::dividend(symbol, ex_date, CorporateActionKind::SpecialDividend, amount)
+    }
+
+    fn dividend(
+        symbol: &str,
+        ex_date: DateTime<Utc>,
+        kind: CorporateActionKind,
+        amount: Money,
+    ) -> CorporateAction {
+        if amount <= Money::zero() {
+            panic!("dividend must be > 0.0 (got {})", amount);
+        }
+
+        CorporateAction {
+            symbol: Rc::from(symbol),
+            ex_date: ex_date,
+            kind: kind,
+            amount: amount,
+        }
+    }
+
+    pub fn split(symbol: &str, ex_date: DateTime<Utc>, to: i32, from: i32) -> CorporateAction {
+        if to <= 0 || from <= 0 {
+            panic!("split ratio must be > 0 (got {}:{})", to, from);
+        }
+
+        CorporateAction {
+            symbol: Rc::from(symbol),
+            ex_date: ex_date,
+            kind: CorporateActionKind::Split { to: to, from: from },
+            amount: Money::zero(),
+        }
+    }
+
+    pub fn symbol(&self) -> Rc<str> {
+        Rc::clone(&self.symbol)
+    }
+
+    pub fn ex_date(&self) -> DateTime<Utc> {
+        self.ex_date
+    }
+
+    pub fn kind(&self) -> CorporateActionKind {
+        self.kind
+    }
+
+    pub fn amount(&self) -> Money {
+        self.amount
+    }
+
+    pub fn is_dividend(&self) -> bool {
+        !matches!(self.kind, CorporateActionKind::Split { .. })
+    }
+
+    // true when ex_date falls after `after` and on or before `until`
+    pub fn goes_ex_between(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> bool {
+        let d = self.ex_date.num_days_from_ce();
+
+        d > after.num_days_from_ce() && d <= until.num_days_from_ce()
+    }
+
+    pub fn summary(&self) -> String {
+        let what = match self.kind {
+            CorporateActionKind::CashDividend => format!("{} dividend", self.amount),
+            CorporateActionKind::SpecialDividend => {
+                format!("{} special dividend", self.amount)
+            }
+            CorporateActionKind::Split { to, from } => format!("{}:{} split", to, from),
+        };
+
+        format!(
+            "{} {} (ex-date {})",
+            self.symbol,
+            what,
+            self.ex_date.format("%Y-%m-%d"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_goes_ex_between() {
+        let day = |d| Utc.ymd(2014, 6, d).and_hms(0, 0, 0);
+        let split = CorporateAction::split("AAPL", day(9), 7, 1);
+
+        assert!(!split.goes_ex_between(day(5), day(6)));
+        assert!(split.goes_ex_between(day(6), day(9)));
+        assert!(!split.goes_ex_between(day(9), day(10)));
+        assert_eq!(split.summary(), "AAPL 7:1 split (ex-date 2014-06-09)");
+    }
+}
@@ -0,0 +1,76 @@
+extern crate chrono;
+use self::chrono::prelude::*;
+use self::chrono::LocalResult;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Money's Display adds a dollar sign and commas, which a spreadsheet won't parse
+pub fn decimal(amount: Money) -> String {
+    let cents = amount.raw_value();
+    let sign = if cents < 0 { "-" } else { "" };
+
+    format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+// for free text (option names, references, etc.) that could contain a comma
+pub fn quoted(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+// The fields of every line that holds data, trimmed, along with its line
+// number (from 1).  Blank lines are skipped, as is a header row: the files we
+// read all have a date in `date_column`, so a line without a digit there
+// isn't data.
+pub fn rows<'a, I>(lines: I, date_column: usize) -> Vec<(usize, Vec<&'a str>)>
+where
+    I: Iterator<Item = &'a str>,
+{
+    lines
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim().split(',').map(|f| f.trim()).collect()))
+        .filter(|(_, fields): &(usize, Vec<&str>)| match fields.get(date_column) {
+            Some(f) => f.starts_with(|c: char| c.is_ascii_digit()),
+            None => false,
+        })
+        .collect()
+}
+
+// "YYYY-MM-DD"
+pub fn parse_date(s: &str) -> Result<DateTime<Utc>, String> {
+    let parts: Vec<u32> = s.split('-').filter_map(|p| p.parse().ok()).collect();
+
+    if parts.len() != 3 {
+        return Err(format!("expected a YYYY-MM-DD date, got '{}'", s));
+    }
+
+    match Utc.ymd_opt(parts[0] as i32, parts[1], parts[2]) {
+        LocalResult::Single(date) => Ok(date.and_hms(0, 0, 0)),
+        _ => Err(format!("'{}' isn't a date", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_decimal() {
+        assert_eq!(decimal(Money::new(1_234, 5)), "1234.05");
+        assert_eq!(decimal(Money::zero() - Money::new(0, 50)), "-0.50");
+        assert_eq!(quoted("PMCC \"#1\""), "\"PMCC \"\"#1\"\"\"");
+    }
+
+    #[test]
+    fn test_rows() {
+        let lines = vec!["date,close", "", "2013-01-02, 50.0", "  ", "2013-01-03,51.0"];
+        let rows = rows(lines.into_iter(), 0);
+
+        assert_eq!(rows, vec![(3, vec!["2013-01-02", "50.0"]), (5, vec!["2013-01-03", "51.0"])]);
+
+        assert!(parse_date("2013-01-02") == Ok(Utc.ymd(2013, 1, 2).and_hms(0, 0, 0)));
+        assert!(parse_date("2013-02-30").is_err());
+        assert!(parse_date("2013-1x-02").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use assassin::equity_point::EquityPoint;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Equity curves for tests, one point per day from 2013-01-02.  Each point
+// is all cash with one position open, unless the open positions are given.
+pub struct EquityCurveBuilder {
+    balances: Vec<i32>,
+    open_positions: Vec<usize>,
+}
+
+impl EquityCurveBuilder {
+    // whole dollars of cash at the end of each day
+    pub fn new(balances: &[i32]) -> EquityCurveBuilder {
+        EquityCurveBuilder {
+            balances: balances.to_vec(),
+            open_positions: vec![1; balances.len()],
+        }
+    }
+
+    pub fn with_open_positions(mut self, open_positions: &[usize]) -> EquityCurveBuilder {
+        self.open_positions = open_positions.to_vec();
+        self
+    }
+
+    pub fn build(self) -> Vec<EquityPoint> {
+        self.balances
+            .iter()
+            .zip(self.open_positions.iter())
+            .enumerate()
+            .map(|(i, (b, p))| {
+                EquityPoint::new(
+                    Utc.ymd(2013, 1, 2 + i as u32).and_hms(0, 0, 0),
+                    Money::new(*b, 0),
+                    Money::zero(),
+                    Money::zero(),
+                    *p,
+                )
+            })
+            .collect()
+    }
+}
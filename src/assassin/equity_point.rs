@@ -0,0 +1,91 @@
+use assassin::csv::decimal;
+use assassin::json::Json;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// The state of the account at the close of a trading day.
+#[derive(Clone)]
+pub struct EquityPoint {
+    date: DateTime<Utc>,
+    cash: Money,
+    // of the options and shares held, valued with the broker's MarkMethod
+    market_value: Money,
+    // the initial margin requirement
+    margin_used: Money,
+    // option and stock positions
+    open_positions: usize,
+}
+
+impl EquityPoint {
+    pub fn new(
+        date: DateTime<Utc>,
+        cash: Money,
+        market_value: Money,
+        margin_used: Money,
+        open_positions: usize,
+    ) -> EquityPoint {
+        EquityPoint {
+            date: date,
+            cash: cash,
+            market_value: market_value,
+            margin_used: margin_used,
+            open_positions: open_positions,
+        }
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    pub fn cash(&self) -> Money {
+        self.cash
+    }
+
+    pub fn market_value(&self) -> Money {
+        self.market_value
+    }
+
+    // the unrealized account balance
+    pub fn equity(&self) -> Money {
+        self.cash + self.market_value
+    }
+
+    pub fn margin_used(&self) -> Money {
+        self.margin_used
+    }
+
+    pub fn open_positions(&self) -> usize {
+        self.open_positions
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("date", Json::date(self.date)),
+            ("cash", Json::Money(self.cash)),
+            ("market_value", Json::Money(self.market_value)),
+            ("equity", Json::Money(self.equity())),
+            ("margin_used", Json::Money(self.margin_used)),
+            ("open_positions", Json::number(self.open_positions as u32)),
+        ])
+    }
+
+    pub fn csv_header() -> &'static str {
+        "date,cash,market_value,equity,margin_used,open_positions"
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.date.format("%Y-%m-%d"),
+            decimal(self.cash),
+            decimal(self.market_value),
+            decimal(self.equity()),
+            decimal(self.margin_used),
+            self.open_positions,
+        )
+    }
+}
@@ -0,0 +1,140 @@
+use std::rc::Rc;
+
+use assassin::trade_group::TradeGroup;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExerciseKind {
+    // a long option was exercised into shares
+    Exercised,
+    // a short option was assigned into shares
+    Assigned,
+    // a short option was assigned before expiration
+    EarlyAssigned,
+    // an in-the-money option on a cash-settled index paid out its intrinsic value
+    CashSettled,
+    ExpiredWorthless,
+}
+
+// Records what happened to an option position at (or before) expiration.
+#[derive(Clone)]
+pub struct ExerciseEvent {
+    date: DateTime<Utc>,
+    option_name: Rc<str>,
+    symbol: Rc<str>,
+    kind: ExerciseKind,
+    // signed like Position::quantity() (long > 0, short < 0)
+    contracts: i32,
+    strike_price: Money,
+    underlying_price: Money,
+    // shares received (> 0) or delivered (< 0)
+    shares: i32,
+    // cash received (> 0) or paid (< 0) to settle the event
+    cash: Money,
+    // of the position
+    group: Option<TradeGroup>,
+}
+
+impl ExerciseEvent {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        date: DateTime<Utc>,
+        option_name: Rc<str>,
+        symbol: Rc<str>,
+        kind: ExerciseKind,
+        contracts: i32,
+        strike_price: Money,
+        underlying_price: Money,
+        shares: i32,
+        cash: Money,
+    ) -> ExerciseEvent {
+        ExerciseEvent {
+            date: date,
+            option_name: option_name,
+            symbol: symbol,
+            kind: kind,
+            contracts: contracts,
+            strike_price: strike_price,
+            underlying_price: underlying_price,
+            shares: shares,
+            cash: cash,
+            group: None,
+        }
+    }
+
+    pub fn with_group(mut self, group: Option<TradeGroup>) -> ExerciseEvent {
+        self.group = group;
+
+        self
+    }
+
+    pub fn group(&self) -> Option<TradeGroup> {
+        self.group.clone()
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    #[allow(dead_code)]
+    pub fn option_name(&self) -> Rc<str> {
+        Rc::clone(&self.option_name)
+    }
+
+    pub fn symbol(&self) -> Rc<str> {
+        Rc::clone(&self.symbol)
+    }
+
+    #[allow(dead_code)]
+    pub fn kind(&self) -> ExerciseKind {
+        self.kind
+    }
+
+    #[allow(dead_code)]
+    pub fn contracts(&self) -> i32 {
+        self.contracts
+    }
+
+    #[allow(dead_code)]
+    pub fn strike_price(&self) -> Money {
+        self.strike_price
+    }
+
+    #[allow(dead_code)]
+    pub fn underlying_price(&self) -> Money {
+        self.underlying_price
+    }
+
+    pub fn shares(&self) -> i32 {
+        self.shares
+    }
+
+    pub fn cash(&self) -> Money {
+        self.cash
+    }
+
+    // "AAPL20130215C0052000: ASSIGNED -5 contracts @ $520.00 (underlying $525.10)"
+    pub fn summary(&self) -> String {
+        let kind = match self.kind {
+            ExerciseKind::Exercised => "EXERCISED",
+            ExerciseKind::Assigned => "ASSIGNED",
+            ExerciseKind::EarlyAssigned => "ASSIGNED EARLY",
+            ExerciseKind::CashSettled => "CASH SETTLED",
+            ExerciseKind::ExpiredWorthless => "EXPIRED WORTHLESS",
+        };
+
+        format!(
+            "{}: {} {} contracts @ {} (underlying {})",
+            self.option_name,
+            kind,
+            self.contracts,
+            self.strike_price,
+            self.underlying_price,
+        )
+    }
+}
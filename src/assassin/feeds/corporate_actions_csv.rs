@@ -0,0 +1,139 @@
+use std::io::BufReader;
+use std::io::BufRead;
+use std::fs::File;
+
+use assassin::corporate_action::CorporateAction;
+use assassin::csv;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Corporate actions read from a CSV file, one per line:
+//
+//   Symbol,ExDate,Action,Value
+//   AAPL,2014-05-09,dividend,3.29
+//   AAPL,2014-06-09,split,7:1
+//   COST,2012-12-06,special,7.00
+#[allow(dead_code)]
+pub struct CorporateActionsCsv {
+    actions: Vec<CorporateAction>,
+}
+
+#[allow(dead_code)]
+impl CorporateActionsCsv {
+    pub fn new(filename: &str) -> Result<CorporateActionsCsv, String> {
+        let file = File::open(filename).map_err(|e| format!("{}: {}", filename, e))?;
+
+        let lines: Vec<String> = BufReader::new(file)
+            .lines()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("{}: {}", filename, e))?;
+
+        CorporateActionsCsv::from_lines(lines.iter().map(|l| l.as_str()))
+            .map_err(|e| format!("{}: {}", filename, e))
+    }
+
+    pub fn from_lines<'a, I>(lines: I) -> Result<CorporateActionsCsv, String>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut actions = vec![];
+
+        for (number, v) in csv::rows(lines, 1) {
+            let action = parse_action(&v).map_err(|e| format!("line {}: {}", number, e))?;
+            actions.push(action);
+        }
+
+        actions.sort_by_key(|a| a.ex_date());
+
+        Ok(CorporateActionsCsv { actions: actions })
+    }
+
+    pub fn actions(self) -> Vec<CorporateAction> {
+        self.actions
+    }
+}
+
+fn parse_action(v: &[&str]) -> Result<CorporateAction, String> {
+    if v.len() != 4 {
+        return Err("expected Symbol,ExDate,Action,Value".to_string());
+    }
+
+    let ex_date = csv::parse_date(v[1])?;
+
+    match v[2] {
+        "dividend" => Ok(CorporateAction::cash_dividend(v[0], ex_date, parse_dividend(v[3])?)),
+        "special" => Ok(CorporateAction::special_dividend(v[0], ex_date, parse_dividend(v[3])?)),
+        "split" => {
+            let ratio: Vec<i32> = v[3].split(':').map(|p| p.parse().unwrap_or(0)).collect();
+
+            if ratio.len() != 2 || ratio.iter().any(|&r| r <= 0) {
+                return Err(format!("expected a split ratio like 7:1, got '{}'", v[3]));
+            }
+
+            Ok(CorporateAction::split(v[0], ex_date, ratio[0], ratio[1]))
+        }
+        other => Err(format!("expected 'dividend', 'special' or 'split', got '{}'", other)),
+    }
+}
+
+fn parse_dividend(value: &str) -> Result<Money, String> {
+    let amount = value.parse().map(Money::from_float).unwrap_or(Money::zero());
+
+    if amount <= Money::zero() {
+        return Err(format!("expected a dividend > 0.00, got '{}'", value));
+    }
+
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::corporate_action::CorporateActionKind;
+
+    #[test]
+    fn test_from_lines() {
+        let lines = vec![
+            "Symbol,ExDate,Action,Value",
+            "AAPL,2014-06-09,split,7:1",
+            "AAPL,2014-05-09,dividend,3.29",
+            "",
+        ];
+        let actions = CorporateActionsCsv::from_lines(lines.into_iter()).unwrap().actions();
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].kind(), CorporateActionKind::CashDividend);
+        assert!(actions[0].amount() == Money::new(3, 29));
+        assert_eq!(actions[1].kind(), CorporateActionKind::Split { to: 7, from: 1 });
+    }
+
+    #[test]
+    fn test_malformed_rows() {
+        let error = |line| match CorporateActionsCsv::from_lines(vec![line].into_iter()) {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => e,
+        };
+
+        assert_eq!(error("AAPL,2014-06-09,split"), "line 1: expected Symbol,ExDate,Action,Value");
+        assert_eq!(error("AAPL,2014-06-31,split,7:1"), "line 1: '2014-06-31' isn't a date");
+        assert_eq!(
+            error("AAPL,2014-06-09,split,7:0"),
+            "line 1: expected a split ratio like 7:1, got '7:0'"
+        );
+        assert_eq!(
+            error("AAPL,2014-06-09,split,7"),
+            "line 1: expected a split ratio like 7:1, got '7'"
+        );
+        assert_eq!(
+            error("AAPL,2014-05-09,dividend,x"),
+            "line 1: expected a dividend > 0.00, got 'x'"
+        );
+        assert_eq!(
+            error("AAPL,2014-05-09,merger,1"),
+            "line 1: expected 'dividend', 'special' or 'split', got 'merger'"
+        );
+    }
+}
@@ -1 +1,2 @@
+pub mod corporate_actions_csv;
 pub mod discount_option_data;
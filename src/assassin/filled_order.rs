@@ -1,7 +1,11 @@
 use std::rc::Rc;
 
+use assassin::csv::{decimal, quoted};
+use assassin::json::Json;
 use assassin::order::Order;
+use assassin::position::PositionKey;
 use assassin::quote::Quote;
+use assassin::trade_group::TradeGroup;
 
 extern crate chrono;
 use self::chrono::prelude::*;
@@ -19,6 +23,11 @@ pub struct FilledOrder {
     commission: Money,
 
     closed_by_broker: bool,
+    combo_id: Option<u32>,
+    // the SubmittedOrder this filled, None for orders the broker placed itself
+    order_id: Option<u32>,
+    // shares per contract, which is only ever not 100 after a corporate action
+    multiplier: i32,
 }
 
 impl FilledOrder {
@@ -36,6 +45,9 @@ impl FilledOrder {
             fill_date: fill_date,
             commission: Money::zero(), // TODO: pass in commission and set in this function
             closed_by_broker: false,
+            combo_id: None,
+            order_id: None,
+            multiplier: 100,
         };
 
         filled_order
@@ -49,6 +61,14 @@ impl FilledOrder {
         self.commission = commish;
     }
 
+    pub fn quote(&self) -> &Quote {
+        &self.fill_quote
+    }
+
+    pub fn fill_date(&self) -> DateTime<Utc> {
+        self.fill_date
+    }
+
     pub fn commission(&self) -> Money {
         self.commission
     }
@@ -65,10 +85,34 @@ impl FilledOrder {
         self.closed_by_broker = true;
     }
 
+    // set when the order was filled as one leg of a ComboOrder
+    pub fn combo_id(&self) -> Option<u32> {
+        self.combo_id
+    }
+
+    pub fn set_combo_id(&mut self, id: u32) {
+        self.combo_id = Some(id);
+    }
+
+    pub fn order_id(&self) -> Option<u32> {
+        self.order_id
+    }
+
+    pub fn set_order_id(&mut self, id: u32) {
+        self.order_id = Some(id);
+    }
+
+    pub fn multiplier(&self) -> i32 {
+        self.multiplier
+    }
+
+    pub fn set_multiplier(&mut self, multiplier: i32) {
+        self.multiplier = multiplier;
+    }
+
     // TODO: double check that this is doing the right thing
     pub fn cost_basis(&self) -> Money {
-        // TODO: make 100 a constant somewhere
-        self.fill_price * 100 * self.order.quantity()
+        self.fill_price * self.multiplier * self.order.quantity()
     }
 
     pub fn canonical_cost_basis(&self) -> Money {
@@ -86,11 +130,71 @@ impl FilledOrder {
             quote.ask()
         };
 
-        price * 100 * self.canonical_quantity()
+        price * self.multiplier * self.canonical_quantity()
+    }
+
+    // what filling away from the midpoint of the quote cost (> 0) or saved (< 0)
+    pub fn slippage(&self) -> Money {
+        let difference = self.fill_price - self.fill_quote.midpoint_price();
+        let slippage = difference * self.multiplier * self.quantity();
+
+        if self.is_buy() {
+            slippage
+        } else {
+            Money::zero() - slippage
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("date", Json::date(self.fill_date)),
+            ("option_name", Json::string(&self.option_name())),
+            ("group", Json::optional(self.group(), |g| Json::String(g.to_string()))),
+            ("action", Json::string(self.buy_or_sell_string())),
+            ("open", Json::Bool(self.is_open())),
+            ("quantity", Json::number(self.quantity())),
+            ("price", Json::Money(self.fill_price)),
+            ("multiplier", Json::number(self.multiplier)),
+            ("cost_basis", Json::Money(self.canonical_cost_basis())),
+            ("commission", Json::Money(self.commission)),
+            ("closed_by_broker", Json::Bool(self.closed_by_broker)),
+            ("combo_id", Json::optional(self.combo_id, Json::number)),
+            ("order_id", Json::optional(self.order_id, Json::number)),
+        ])
+    }
+
+    pub fn csv_header() -> &'static str {
+        "date,option_name,group,action,open,quantity,price,multiplier,cost_basis,commission,\
+         closed_by_broker,combo_id,order_id"
+    }
+
+    pub fn to_csv(&self) -> String {
+        let optional = |id: Option<u32>| id.map(|i| i.to_string()).unwrap_or_default();
+
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.fill_date.format("%Y-%m-%d"),
+            quoted(&self.option_name()),
+            quoted(&self.group().map(|g| g.to_string()).unwrap_or_default()),
+            self.buy_or_sell_string(),
+            self.is_open(),
+            self.quantity(),
+            decimal(self.fill_price),
+            self.multiplier,
+            decimal(self.canonical_cost_basis()),
+            decimal(self.commission),
+            self.closed_by_broker,
+            optional(self.combo_id),
+            optional(self.order_id),
+        )
     }
 
     // ===== proxied functions ==========================================================
 
+    pub fn summary(&self) -> String {
+        self.order.summary()
+    }
+
     pub fn buy_or_sell_string(&self) -> &str {
         self.order.buy_or_sell_string()
     }
@@ -103,6 +207,23 @@ impl FilledOrder {
         self.order.option_name()
     }
 
+    pub fn symbol(&self) -> &str {
+        self.order.symbol()
+    }
+
+    pub fn lot_ids(&self) -> &Vec<u32> {
+        self.order.lot_ids()
+    }
+
+    pub fn group(&self) -> Option<TradeGroup> {
+        self.order.group()
+    }
+
+    // the key of the Position this fill belongs to
+    pub fn position_key(&self) -> PositionKey {
+        (self.group(), self.option_name())
+    }
+
     pub fn margin_requirement(&self, price: Money) -> Money {
         self.order.margin_requirement(price)
     }
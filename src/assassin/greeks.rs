@@ -0,0 +1,203 @@
+use std::ops::Add;
+
+use assassin::pricing::{self, DAYS_PER_YEAR};
+use assassin::quote::Quote;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// The exposures of a position, or of everything held on an underlying or in
+// the account.  Everything is scaled by quantity and multiplier so that
+// holdings add up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Greeks {
+    // in shares of the underlying (long > 0, short < 0)
+    delta: f64,
+    // what a $1 move in the underlying does to the value
+    dollar_delta: f64,
+    // change in (share) delta for a $1 move in the underlying
+    gamma: f64,
+    // dollars per day
+    theta: f64,
+    // dollars per 1 point move in implied volatility
+    vega: f64,
+    // dollar delta scaled by the underlying's beta to the market
+    beta_weighted_delta: f64,
+}
+
+impl Greeks {
+    pub fn zero() -> Greeks {
+        Greeks {
+            delta: 0.0,
+            dollar_delta: 0.0,
+            gamma: 0.0,
+            theta: 0.0,
+            vega: 0.0,
+            beta_weighted_delta: 0.0,
+        }
+    }
+
+    // delta, gamma and vega come from the feed.  the feed doesn't supply
+    // theta so it's worked out from the quote's implied volatility.
+    pub fn option(
+        quote: &Quote,
+        contracts: i32,
+        multiplier: i32,
+        date: DateTime<Utc>,
+        rate: f64,
+        beta: f64,
+    ) -> Greeks {
+        let shares = (contracts * multiplier) as f64;
+        let spot = dollars(quote.underlying_price());
+
+        let theta = pricing::theta(
+            quote.is_call(),
+            spot,
+            dollars(quote.strike_price()),
+            quote.days_to_expiration(date) as f64 / DAYS_PER_YEAR,
+            rate,
+            quote.implied_volatility() as f64,
+        );
+
+        let delta = quote.delta() as f64 * shares;
+
+        Greeks {
+            delta: delta,
+            dollar_delta: delta * spot,
+            gamma: quote.gamma() as f64 * shares,
+            theta: theta / DAYS_PER_YEAR * shares,
+            vega: quote.vega() as f64 * shares,
+            beta_weighted_delta: delta * spot * beta,
+        }
+    }
+
+    pub fn stock(shares: i32, price: Money, beta: f64) -> Greeks {
+        let delta = shares as f64;
+
+        Greeks {
+            delta: delta,
+            dollar_delta: delta * dollars(price),
+            beta_weighted_delta: delta * dollars(price) * beta,
+            ..Greeks::zero()
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    pub fn dollar_delta(&self) -> f64 {
+        self.dollar_delta
+    }
+
+    #[allow(dead_code)]
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    pub fn theta(&self) -> f64 {
+        self.theta
+    }
+
+    pub fn vega(&self) -> f64 {
+        self.vega
+    }
+
+    pub fn beta_weighted_delta(&self) -> f64 {
+        self.beta_weighted_delta
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "delta: {:.1} (${:.0}, ${:.0} beta weighted) gamma: {:.2} theta: {:.2} vega: {:.2}",
+            self.delta,
+            self.dollar_delta,
+            self.beta_weighted_delta,
+            self.gamma,
+            self.theta,
+            self.vega,
+        )
+    }
+
+    pub fn csv_header() -> &'static str {
+        "delta,dollar_delta,gamma,theta,vega,beta_weighted_delta"
+    }
+
+    pub fn to_csv(self) -> String {
+        format!(
+            "{:.4},{:.2},{:.4},{:.2},{:.2},{:.2}",
+            self.delta,
+            self.dollar_delta,
+            self.gamma,
+            self.theta,
+            self.vega,
+            self.beta_weighted_delta,
+        )
+    }
+}
+
+impl Add for Greeks {
+    type Output = Greeks;
+
+    fn add(self, other: Greeks) -> Greeks {
+        Greeks {
+            delta: self.delta + other.delta,
+            dollar_delta: self.dollar_delta + other.dollar_delta,
+            gamma: self.gamma + other.gamma,
+            theta: self.theta + other.theta,
+            vega: self.vega + other.vega,
+            beta_weighted_delta: self.beta_weighted_delta + other.beta_weighted_delta,
+        }
+    }
+}
+
+fn dollars(amount: Money) -> f64 {
+    amount.raw_value() as f64 / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::quote_builder::QuoteBuilder;
+
+    fn quote() -> Quote {
+        QuoteBuilder::new()
+            .expiring(2013, 4, 1)
+            .with_strike(50)
+            .with_bid_ask(Money::new(2, 0), Money::new(2, 10))
+            .with_implied_volatility(0.2)
+            .with_greeks(0.5, 0.05, 0.1)
+            .with_underlying(Money::new(49, 0))
+            .build()
+    }
+
+    #[test]
+    fn test_short_option() {
+        let date = Utc.ymd(2013, 1, 2).and_hms(0, 0, 0);
+        let greeks = Greeks::option(&quote(), -2, 100, date, 0.05, 1.5);
+
+        assert!((greeks.delta() - -100.0).abs() < 1e-3);
+        assert!((greeks.dollar_delta() - -4_900.0).abs() < 0.1);
+        assert!((greeks.beta_weighted_delta() - -7_350.0).abs() < 0.1);
+        assert!((greeks.gamma() - -10.0).abs() < 1e-3);
+        assert!((greeks.vega() - -20.0).abs() < 1e-3);
+        // short options earn the decay
+        assert!(greeks.theta() > 0.0);
+    }
+
+    #[test]
+    fn test_add() {
+        let stock = Greeks::stock(100, Money::new(49, 0), 1.0);
+        let total = stock + Greeks::stock(-40, Money::new(49, 0), 1.0);
+
+        assert_eq!(total.delta(), 60.0);
+        assert_eq!(total.dollar_delta(), 2_940.0);
+        assert_eq!(total.theta(), 0.0);
+    }
+}
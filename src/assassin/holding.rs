@@ -0,0 +1,134 @@
+use std::rc::Rc;
+
+use assassin::quote::Quote;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HoldingKind {
+    Stock,
+    Call,
+    Put,
+}
+
+// A snapshot of something held in the account, valued at the price it could
+// be liquidated at.  This is what margin is calculated from, so hypothetical
+// holdings (i.e., what the account would look like after an order fills) can
+// be evaluated without touching any Positions.
+#[derive(Clone)]
+pub struct Holding {
+    name: Rc<str>,
+    symbol: Rc<str>,
+    kind: HoldingKind,
+    // contracts for options, shares for stock.  negative when short.
+    quantity: i32,
+    // per share
+    price: Money,
+    strike_price: Money,
+    expiration_date: DateTime<Utc>,
+    underlying_price: Money,
+    implied_volatility: f32,
+    // shares per unit of quantity
+    multiplier: i32,
+    date: DateTime<Utc>,
+}
+
+impl Holding {
+    // `price` is what the option is marked at per share, which the broker
+    // takes from its MarkMethod so margin sees the same equity the account
+    // balance does.  `multiplier` is the shares each contract delivers, which
+    // is only something other than 100 after a corporate action adjusted the
+    // contract.
+    pub fn option(quote: &Quote, quantity: i32, price: Money, multiplier: i32) -> Holding {
+        Holding {
+            name: quote.name(),
+            symbol: quote.symbol(),
+            kind: if quote.is_call() {
+                HoldingKind::Call
+            } else {
+                HoldingKind::Put
+            },
+            quantity: quantity,
+            price: price,
+            strike_price: quote.strike_price(),
+            expiration_date: quote.expiration_date(),
+            underlying_price: quote.underlying_price(),
+            implied_volatility: quote.implied_volatility(),
+            multiplier: multiplier,
+            date: quote.date(),
+        }
+    }
+
+    pub fn stock(symbol: Rc<str>, quantity: i32, price: Money, date: DateTime<Utc>) -> Holding {
+        Holding {
+            name: Rc::clone(&symbol),
+            symbol: symbol,
+            kind: HoldingKind::Stock,
+            quantity: quantity,
+            price: price,
+            strike_price: Money::zero(),
+            expiration_date: date,
+            underlying_price: price,
+            implied_volatility: 0.0,
+            multiplier: 1,
+            date: date,
+        }
+    }
+
+    pub fn name(&self) -> Rc<str> {
+        Rc::clone(&self.name)
+    }
+
+    pub fn symbol(&self) -> Rc<str> {
+        Rc::clone(&self.symbol)
+    }
+
+    pub fn kind(&self) -> HoldingKind {
+        self.kind
+    }
+
+    pub fn is_stock(&self) -> bool {
+        self.kind == HoldingKind::Stock
+    }
+
+    pub fn quantity(&self) -> i32 {
+        self.quantity
+    }
+
+    pub fn price(&self) -> Money {
+        self.price
+    }
+
+    pub fn strike_price(&self) -> Money {
+        self.strike_price
+    }
+
+    pub fn expiration_date(&self) -> DateTime<Utc> {
+        self.expiration_date
+    }
+
+    pub fn underlying_price(&self) -> Money {
+        self.underlying_price
+    }
+
+    pub fn implied_volatility(&self) -> f32 {
+        self.implied_volatility
+    }
+
+    pub fn days_to_expiration(&self) -> i32 {
+        self.expiration_date.num_days_from_ce() - self.date.num_days_from_ce()
+    }
+
+    // shares per unit of quantity
+    pub fn multiplier(&self) -> i32 {
+        self.multiplier
+    }
+
+    pub fn market_value(&self) -> Money {
+        self.price * self.multiplier() * self.quantity
+    }
+}
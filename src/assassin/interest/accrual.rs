@@ -0,0 +1,62 @@
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Interest earned on (amount > 0) or charged to (amount < 0) the cash balance
+// for the days between one trading day and the next.
+#[derive(Clone)]
+pub struct InterestAccrual {
+    date: DateTime<Utc>,
+    balance: Money,
+    annual_rate: f64,
+    days: i32,
+    amount: Money,
+}
+
+impl InterestAccrual {
+    pub fn new(
+        date: DateTime<Utc>,
+        balance: Money,
+        annual_rate: f64,
+        days: i32,
+        amount: Money,
+    ) -> InterestAccrual {
+        InterestAccrual {
+            date: date,
+            balance: balance,
+            annual_rate: annual_rate,
+            days: days,
+            amount: amount,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    #[allow(dead_code)]
+    pub fn balance(&self) -> Money {
+        self.balance
+    }
+
+    #[allow(dead_code)]
+    pub fn annual_rate(&self) -> f64 {
+        self.annual_rate
+    }
+
+    #[allow(dead_code)]
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    pub fn amount(&self) -> Money {
+        self.amount
+    }
+
+    pub fn is_credit(&self) -> bool {
+        self.amount > Money::zero()
+    }
+}
@@ -0,0 +1,25 @@
+use assassin::traits::*;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+#[allow(dead_code)]
+pub struct ConstantRate {
+    annual_rate: f64,
+}
+
+#[allow(dead_code)]
+impl ConstantRate {
+    // 0.05 is 5% a year
+    pub fn new(annual_rate: f64) -> ConstantRate {
+        ConstantRate {
+            annual_rate: annual_rate,
+        }
+    }
+}
+
+impl InterestRate for ConstantRate {
+    fn annual_rate_on(&self, _date: DateTime<Utc>) -> f64 {
+        self.annual_rate
+    }
+}
@@ -0,0 +1,108 @@
+use std::io::BufReader;
+use std::io::BufRead;
+use std::fs::File;
+
+use assassin::csv;
+use assassin::traits::*;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+// Daily annual rates read from a CSV file, one "YYYY-MM-DD,rate" per line
+// (0.05 is 5%).  Days missing from the file (weekends, holidays) use the most
+// recent rate before them.
+#[allow(dead_code)]
+pub struct CsvRateSeries {
+    // sorted by date
+    rates: Vec<(i32, f64)>,
+}
+
+#[allow(dead_code)]
+impl CsvRateSeries {
+    pub fn new(filename: &str) -> Result<CsvRateSeries, String> {
+        let file = File::open(filename).map_err(|e| format!("{}: {}", filename, e))?;
+
+        let lines: Vec<String> = BufReader::new(file)
+            .lines()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("{}: {}", filename, e))?;
+
+        CsvRateSeries::from_lines(lines.iter().map(|l| l.as_str()))
+            .map_err(|e| format!("{}: {}", filename, e))
+    }
+
+    pub fn from_lines<'a, I>(lines: I) -> Result<CsvRateSeries, String>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut rates = vec![];
+
+        for (number, v) in csv::rows(lines, 0) {
+            if v.len() != 2 {
+                return Err(format!("line {}: expected date,rate", number));
+            }
+
+            let date = csv::parse_date(v[0]).map_err(|e| format!("line {}: {}", number, e))?;
+            let rate: f64 = v[1]
+                .parse()
+                .map_err(|_| format!("line {}: '{}' isn't a rate", number, v[1]))?;
+
+            rates.push((date.num_days_from_ce(), rate));
+        }
+
+        if rates.is_empty() {
+            return Err("no rates found".to_string());
+        }
+
+        rates.sort_by_key(|r| r.0);
+
+        Ok(CsvRateSeries { rates: rates })
+    }
+}
+
+impl InterestRate for CsvRateSeries {
+    fn annual_rate_on(&self, date: DateTime<Utc>) -> f64 {
+        let day = date.num_days_from_ce();
+
+        // before the series starts we use the first rate we have
+        match self.rates.iter().rev().find(|&&(d, _)| d <= day) {
+            Some(&(_, rate)) => rate,
+            None => self.rates[0].1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rate_on() {
+        let lines = vec!["date,rate", "2013-01-02,0.01", "2013-01-04,0.02", ""];
+        let series = CsvRateSeries::from_lines(lines.into_iter()).unwrap();
+
+        let day = |d| Utc.ymd(2013, 1, d).and_hms(0, 0, 0);
+
+        assert_eq!(series.annual_rate_on(day(1)), 0.01);
+        assert_eq!(series.annual_rate_on(day(3)), 0.01);
+        assert_eq!(series.annual_rate_on(day(4)), 0.02);
+        assert_eq!(series.annual_rate_on(day(7)), 0.02);
+    }
+
+    #[test]
+    fn test_malformed_rows() {
+        let error = |lines: Vec<&'static str>| match CsvRateSeries::from_lines(lines.into_iter()) {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => e,
+        };
+
+        assert_eq!(error(vec!["date,rate", "2013-01-02,x"]), "line 2: 'x' isn't a rate");
+        assert_eq!(error(vec!["2013-01-02,0.01,0.02"]), "line 1: expected date,rate");
+        assert_eq!(
+            error(vec!["2013-13-02,0.01"]),
+            "line 1: '2013-13-02' isn't a date"
+        );
+        assert_eq!(error(vec!["date,rate", ""]), "no rates found");
+    }
+}
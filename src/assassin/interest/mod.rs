@@ -0,0 +1,3 @@
+pub mod accrual;
+pub mod constant;
+pub mod csv_series;
@@ -0,0 +1,150 @@
+use std::rc::Rc;
+
+use assassin::csv::{decimal, quoted};
+use assassin::json::Json;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JournalEntryKind {
+    // option premium paid (< 0) or received (> 0) when an order fills
+    Premium,
+    Commission,
+    Interest,
+    Dividend,
+    // shares bought or sold at the strike when an option is exercised or
+    // assigned, and cash-settled options paying out their intrinsic value
+    Settlement,
+    // shares bought or sold outside of exercise/assignment
+    StockTrade,
+    // the fractional share left over from a split
+    CashInLieu,
+}
+
+impl JournalEntryKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            JournalEntryKind::Premium => "premium",
+            JournalEntryKind::Commission => "commission",
+            JournalEntryKind::Interest => "interest",
+            JournalEntryKind::Dividend => "dividend",
+            JournalEntryKind::Settlement => "settlement",
+            JournalEntryKind::StockTrade => "stock trade",
+            JournalEntryKind::CashInLieu => "cash in lieu",
+        }
+    }
+}
+
+// A single change to the broker's cash balance.  The broker only ever appends
+// these, so replaying them from the opening balance gives the current one.
+#[derive(Clone)]
+pub struct JournalEntry {
+    id: u32,
+    date: DateTime<Utc>,
+    kind: JournalEntryKind,
+    // credited (> 0) or debited (< 0)
+    amount: Money,
+    // the cash balance after this entry was posted
+    balance: Money,
+    // what the entry was for (an option name, symbol, etc.)
+    reference: Rc<str>,
+}
+
+impl JournalEntry {
+    pub fn new(
+        id: u32,
+        date: DateTime<Utc>,
+        kind: JournalEntryKind,
+        amount: Money,
+        balance: Money,
+        reference: Rc<str>,
+    ) -> JournalEntry {
+        JournalEntry {
+            id: id,
+            date: date,
+            kind: kind,
+            amount: amount,
+            balance: balance,
+            reference: reference,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    pub fn kind(&self) -> JournalEntryKind {
+        self.kind
+    }
+
+    pub fn amount(&self) -> Money {
+        self.amount
+    }
+
+    pub fn balance(&self) -> Money {
+        self.balance
+    }
+
+    #[allow(dead_code)]
+    pub fn reference(&self) -> Rc<str> {
+        Rc::clone(&self.reference)
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("id", Json::number(self.id)),
+            ("date", Json::date(self.date)),
+            ("kind", Json::string(self.kind.name())),
+            ("amount", Json::Money(self.amount)),
+            ("balance", Json::Money(self.balance)),
+            ("reference", Json::string(&self.reference)),
+        ])
+    }
+
+    pub fn csv_header() -> &'static str {
+        "id,date,kind,amount,balance,reference"
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.id,
+            self.date.format("%Y-%m-%d"),
+            self.kind.name(),
+            decimal(self.amount),
+            decimal(self.balance),
+            quoted(&self.reference),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let entry = JournalEntry::new(
+            3,
+            Utc.ymd(2013, 1, 2).and_hms(0, 0, 0),
+            JournalEntryKind::Premium,
+            Money::new(-1_234, 50),
+            Money::new(98_765, 50),
+            Rc::from("BUY 1 \"AAPL\""),
+        );
+
+        assert_eq!(
+            entry.to_csv(),
+            "3,2013-01-02,premium,-1234.50,98765.50,\"BUY 1 \"\"AAPL\"\"\""
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+
+use assassin::csv::decimal;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Just enough JSON to export results without pulling in a serializer.  Keys
+// keep the order they were added in.
+#[derive(Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    // written as null when it isn't finite, since JSON has no NaN or infinity
+    Number(f64),
+    // written as a number of dollars with exactly two decimal places
+    Money(Money),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    pub fn string(s: &str) -> Json {
+        Json::String(s.to_string())
+    }
+
+    // 2013-01-02
+    pub fn date(date: DateTime<Utc>) -> Json {
+        Json::String(date.format("%Y-%m-%d").to_string())
+    }
+
+    pub fn number<T: Into<f64>>(n: T) -> Json {
+        Json::Number(n.into())
+    }
+
+    pub fn optional<T, F: Fn(T) -> Json>(value: Option<T>, to_json: F) -> Json {
+        match value {
+            Some(v) => to_json(v),
+            None => Json::Null,
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => write!(f, "null"),
+            Json::Money(m) => write!(f, "{}", decimal(m)),
+            Json::String(ref s) => write_string(f, s),
+            Json::Array(ref values) => {
+                write!(f, "[")?;
+
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", v)?;
+                }
+
+                write!(f, "]")
+            }
+            Json::Object(ref fields) => {
+                write!(f, "{{")?;
+
+                for (i, &(key, ref value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_to_string() {
+        let json = Json::Object(vec![
+            ("name", Json::string("AAPL \"weekly\"\n")),
+            ("date", Json::date(Utc.ymd(2013, 1, 2).and_hms(0, 0, 0))),
+            ("profit", Json::Money(Money::zero() - Money::new(1_234, 5))),
+            ("contracts", Json::number(3)),
+            ("profit_factor", Json::Number(f64::INFINITY)),
+            ("group", Json::optional(None::<u32>, Json::number)),
+            ("open", Json::Array(vec![Json::Bool(true), Json::Number(0.25)])),
+        ]);
+
+        assert_eq!(
+            json.to_string(),
+            "{\"name\":\"AAPL \\\"weekly\\\"\\n\",\"date\":\"2013-01-02\",\
+             \"profit\":-1234.05,\"contracts\":3,\"profit_factor\":null,\
+             \"group\":null,\"open\":[true,0.25]}"
+        );
+    }
+}
@@ -0,0 +1,196 @@
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// how many days a lot has to be held for its gain or loss to be long term
+static LONG_TERM_DAYS: i32 = 365;
+
+// Which open lots a closing fill is matched against.
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LotMatching {
+    // oldest lot first
+    Fifo,
+    // newest lot first
+    Lifo,
+    // the lots named on the closing Order (see Order::with_lot_ids()), then
+    // oldest first for anything the named lots don't cover
+    SpecificLot,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HoldingPeriod {
+    ShortTerm,
+    LongTerm,
+}
+
+// Contracts opened by a single fill that haven't been closed yet.
+#[derive(Clone)]
+pub struct Lot {
+    // unique within its Position
+    id: u32,
+    opened: DateTime<Utc>,
+    // signed like Position::quantity() (long > 0, short < 0)
+    quantity: i32,
+    // per share, the same way option prices are quoted
+    price: Money,
+    multiplier: i32,
+}
+
+impl Lot {
+    pub fn new(
+        id: u32,
+        opened: DateTime<Utc>,
+        quantity: i32,
+        price: Money,
+        multiplier: i32,
+    ) -> Lot {
+        Lot {
+            id: id,
+            opened: opened,
+            quantity: quantity,
+            price: price,
+            multiplier: multiplier,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[allow(dead_code)]
+    pub fn opened(&self) -> DateTime<Utc> {
+        self.opened
+    }
+
+    pub fn quantity(&self) -> i32 {
+        self.quantity
+    }
+
+    pub fn price(&self) -> Money {
+        self.price
+    }
+
+    #[allow(dead_code)]
+    pub fn multiplier(&self) -> i32 {
+        self.multiplier
+    }
+
+    // what was paid for (> 0) or received for (< 0) the open contracts
+    pub fn cost_basis(&self) -> Money {
+        self.price * self.multiplier * self.quantity
+    }
+
+    // takes `contracts` (> 0) out of the lot
+    pub fn close(&mut self, contracts: i32, price: Money, date: DateTime<Utc>) -> ClosedLot {
+        if contracts <= 0 || contracts > self.quantity.abs() {
+            panic!(
+                "can't close {} contracts of a lot of {}",
+                contracts,
+                self.quantity.abs()
+            );
+        }
+
+        let quantity = contracts * self.quantity.signum();
+        self.quantity -= quantity;
+
+        ClosedLot {
+            lot_id: self.id,
+            opened: self.opened,
+            closed: date,
+            quantity: quantity,
+            open_price: self.price,
+            close_price: price,
+            multiplier: self.multiplier,
+        }
+    }
+
+    // see Position::apply_split()
+    pub fn apply_split(&mut self, to: i32, from: i32) {
+        if to % from == 0 {
+            self.quantity *= to / from;
+            self.price /= to / from;
+        } else {
+            self.price = self.price * from / to;
+            self.multiplier = self.multiplier * to / from;
+        }
+    }
+}
+
+// The part of a Lot that was closed by a single fill.
+#[derive(Clone)]
+pub struct ClosedLot {
+    lot_id: u32,
+    opened: DateTime<Utc>,
+    closed: DateTime<Utc>,
+    // signed like the Lot it came from
+    quantity: i32,
+    open_price: Money,
+    close_price: Money,
+    multiplier: i32,
+}
+
+impl ClosedLot {
+    pub fn lot_id(&self) -> u32 {
+        self.lot_id
+    }
+
+    #[allow(dead_code)]
+    pub fn opened(&self) -> DateTime<Utc> {
+        self.opened
+    }
+
+    pub fn closed(&self) -> DateTime<Utc> {
+        self.closed
+    }
+
+    pub fn quantity(&self) -> i32 {
+        self.quantity
+    }
+
+    pub fn open_price(&self) -> Money {
+        self.open_price
+    }
+
+    pub fn close_price(&self) -> Money {
+        self.close_price
+    }
+
+    // before commission
+    pub fn realized_profit(&self) -> Money {
+        (self.close_price - self.open_price) * self.multiplier * self.quantity
+    }
+
+    pub fn days_held(&self) -> i32 {
+        self.closed.num_days_from_ce() - self.opened.num_days_from_ce()
+    }
+
+    pub fn holding_period(&self) -> HoldingPeriod {
+        if self.days_held() > LONG_TERM_DAYS {
+            HoldingPeriod::LongTerm
+        } else {
+            HoldingPeriod::ShortTerm
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_close() {
+        let opened = Utc.ymd(2013, 1, 2).and_hms(0, 0, 0);
+        let mut lot = Lot::new(1, opened, -3, Money::new(2, 0), 100);
+
+        let closed = lot.close(2, Money::new(1, 50), Utc.ymd(2014, 1, 3).and_hms(0, 0, 0));
+
+        assert_eq!(lot.quantity(), -1);
+        assert_eq!(closed.quantity(), -2);
+        assert!(closed.realized_profit() == Money::new(100, 0));
+        assert_eq!(closed.holding_period(), HoldingPeriod::LongTerm);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod portfolio;
+pub mod reg_t;
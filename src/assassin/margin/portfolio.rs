@@ -0,0 +1,212 @@
+use std::rc::Rc;
+
+use assassin::holding::{Holding, HoldingKind};
+use assassin::margin_requirement::MarginRequirement;
+use assassin::pricing::{black_scholes, DAYS_PER_YEAR};
+use assassin::traits::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Risk-based (portfolio) margin.
+//
+// Every underlying's holdings are repriced with Black-Scholes across a range
+// of moves in the underlying and shifts in implied volatility.  The
+// requirement is the worst loss across those scenarios, with a minimum per
+// option contract so that positions that look riskless on paper (e.g., far
+// out of the money shorts) still tie something up.
+pub struct PortfolioMargin {
+    // +/- this percent of the underlying...
+    price_range_percent: f64,
+    // ...split into this many steps on either side of the current price
+    price_steps: i32,
+    // relative changes to implied volatility (0.25 is IV * 1.25)
+    volatility_shifts: Vec<f64>,
+    risk_free_rate: f64,
+    minimum_per_contract: Money,
+}
+
+impl PortfolioMargin {
+    // the equity option defaults: +/- 15% in 10 steps with IV shocked 25% up
+    // and down and a minimum of $37.50 per contract
+    pub fn new() -> PortfolioMargin {
+        PortfolioMargin {
+            price_range_percent: 15.0,
+            price_steps: 10,
+            volatility_shifts: vec![-0.25, 0.0, 0.25],
+            risk_free_rate: 0.0,
+            minimum_per_contract: Money::new(37, 50),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_price_range(mut self, percent: f64, steps: i32) -> PortfolioMargin {
+        if percent <= 0.0 || steps < 1 {
+            panic!(
+                "percent must be > 0 and steps >= 1 (got {} and {})",
+                percent,
+                steps
+            );
+        }
+
+        self.price_range_percent = percent;
+        self.price_steps = steps;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_volatility_shifts(mut self, shifts: Vec<f64>) -> PortfolioMargin {
+        if shifts.iter().any(|s| *s <= -1.0) {
+            panic!("volatility shifts must be > -1.0");
+        }
+
+        self.volatility_shifts = shifts;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_risk_free_rate(mut self, rate: f64) -> PortfolioMargin {
+        self.risk_free_rate = rate;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_minimum_per_contract(mut self, minimum: Money) -> PortfolioMargin {
+        self.minimum_per_contract = minimum;
+        self
+    }
+
+    // theoretical value of the holdings if the underlying moved by `price_move`
+    // (0.10 is up 10%) and IV by `volatility_shift`
+    fn scenario_value(&self, holdings: &[&Holding], price_move: f64, volatility_shift: f64) -> f64 {
+        holdings
+            .iter()
+            .map(|h| {
+                let spot = h.underlying_price().raw_value() as f64 / 100.0 * (1.0 + price_move);
+
+                let per_share = match h.kind() {
+                    HoldingKind::Stock => spot,
+                    kind => black_scholes(
+                        kind == HoldingKind::Call,
+                        spot,
+                        h.strike_price().raw_value() as f64 / 100.0,
+                        h.days_to_expiration() as f64 / DAYS_PER_YEAR,
+                        self.risk_free_rate,
+                        h.implied_volatility() as f64 * (1.0 + volatility_shift),
+                    ),
+                };
+
+                per_share * h.multiplier() as f64 * h.quantity() as f64
+            })
+            .sum()
+    }
+
+    fn requirement_for_underlying(&self, holdings: &[&Holding]) -> Money {
+        let base = self.scenario_value(holdings, 0.0, 0.0);
+        let mut worst_loss = 0.0f64;
+
+        for step in -self.price_steps..self.price_steps + 1 {
+            let price_move = self.price_range_percent / 100.0 * step as f64
+                / self.price_steps as f64;
+
+            for shift in &self.volatility_shifts {
+                let loss = base - self.scenario_value(holdings, price_move, *shift);
+                worst_loss = worst_loss.max(loss);
+            }
+        }
+
+        let contracts: i32 = holdings
+            .iter()
+            .filter(|h| !h.is_stock())
+            .map(|h| h.quantity().abs())
+            .sum();
+
+        let worst_loss = Money::from_cents((worst_loss * 100.0).round() as i32);
+        let minimum = self.minimum_per_contract * contracts;
+
+        if worst_loss > minimum {
+            worst_loss
+        } else {
+            minimum
+        }
+    }
+}
+
+impl MarginCalculator for PortfolioMargin {
+    fn name(&self) -> &'static str {
+        "Portfolio"
+    }
+
+    fn requirement_for(&self, holdings: &[Holding]) -> MarginRequirement {
+        let mut symbols: Vec<Rc<str>> = holdings.iter().map(|h| h.symbol()).collect();
+        symbols.sort();
+        symbols.dedup();
+
+        let requirement: Money = symbols
+            .iter()
+            .map(|s| {
+                let hs: Vec<&Holding> = holdings.iter().filter(|h| h.symbol() == *s).collect();
+                self.requirement_for_underlying(&hs)
+            })
+            .sum();
+
+        MarginRequirement::new(requirement, requirement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::margin::reg_t::RegT;
+    use assassin::quote::Quote;
+    use assassin::quote_builder::QuoteBuilder;
+
+    extern crate chrono;
+    use self::chrono::prelude::*;
+
+    fn quote(call: bool, strike: i32, price: Money) -> Quote {
+        QuoteBuilder::new()
+            .expiring(2013, 2, 1)
+            .with_call(call)
+            .with_strike(strike)
+            .with_price(price)
+            .with_implied_volatility(0.3)
+            .build()
+    }
+
+    // the quotes have no spread, so any mark is their price
+    fn option(quote: Quote, quantity: i32, multiplier: i32) -> Holding {
+        Holding::option(&quote, quantity, quote.bid(), multiplier)
+    }
+
+    #[test]
+    fn test_stock_loses_the_full_range() {
+        let stock = Holding::stock(Rc::from("AAPL"), 100, Money::new(100, 0), Utc::now());
+        let requirement = PortfolioMargin::new().requirement_for(&[stock]);
+
+        assert!(requirement.initial() == Money::new(1_500, 0));
+    }
+
+    #[test]
+    fn test_hedged_positions_need_less_than_reg_t() {
+        let stock = Holding::stock(Rc::from("AAPL"), 100, Money::new(100, 0), Utc::now());
+        let put = option(quote(false, 95, Money::new(1, 50)), 1, 100);
+
+        let portfolio = PortfolioMargin::new().requirement_for(&[stock.clone(), put.clone()]);
+        let reg_t = RegT::new().requirement_for(&[stock, put]);
+
+        // can't lose more than the $5 to the put's strike plus the put's value
+        assert!(portfolio.initial() <= Money::new(650, 0));
+        assert!(portfolio.initial() < reg_t.initial());
+    }
+
+    #[test]
+    fn test_minimum_per_contract() {
+        let call = option(quote(true, 200, Money::new(0, 0)), -2, 100);
+        let requirement = PortfolioMargin::new().requirement_for(&[call]);
+
+        assert!(requirement.initial() == Money::new(75, 0));
+    }
+}
@@ -0,0 +1,312 @@
+use std::rc::Rc;
+
+use assassin::holding::{Holding, HoldingKind};
+use assassin::margin_requirement::MarginRequirement;
+use assassin::traits::*;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// one side of the options of a single type on an underlying
+struct Leg {
+    strike_price: Money,
+    expiration_date: DateTime<Utc>,
+    quantity: i32, // always positive
+    price: Money,
+    multiplier: i32,
+}
+
+// Strategy-based margin under Regulation T.
+//
+// Requirements are measured against equity (cash plus the liquidation value of
+// everything held), so the premium received for a short option is already
+// accounted for and isn't added on top of the requirement again.
+//
+// * long options have no loan value and require their full market value
+// * naked calls require 20% of the underlying less the out of the money
+//   amount, with a minimum of 10% of the underlying
+// * naked puts are the same but with a minimum of 10% of the strike
+// * calls covered by the long shares they deliver require nothing beyond the
+//   stock
+// * a short option paired with a long option of the same type that expires
+//   no earlier (verticals, calendars and diagonals like PMCC) requires the
+//   most the pair can lose, which for debit spreads is just its net value
+// * long stock requires 50% initial and 25% maintenance, short stock 50%
+//   initial and 30% maintenance
+pub struct RegT {}
+
+impl RegT {
+    pub fn new() -> RegT {
+        RegT {}
+    }
+
+    fn requirement_for_underlying(&self, holdings: &[&Holding]) -> MarginRequirement {
+        let mut requirement = MarginRequirement::zero();
+
+        let shares: i32 = holdings
+            .iter()
+            .filter(|h| h.is_stock())
+            .map(|h| h.quantity())
+            .sum();
+
+        for h in holdings.iter().filter(|h| h.is_stock()) {
+            requirement = requirement + self.stock_requirement(h);
+        }
+
+        let mut coverable_shares = if shares > 0 { shares } else { 0 };
+
+        for kind in &[HoldingKind::Call, HoldingKind::Put] {
+            let (mut shorts, mut longs) = legs_for(holdings, *kind);
+
+            // cover the most in the money calls with stock first
+            if *kind == HoldingKind::Call {
+                shorts.sort_by_key(|l| l.strike_price);
+
+                for short in shorts.iter_mut() {
+                    let covered = *[short.quantity, coverable_shares / short.multiplier]
+                        .iter()
+                        .min()
+                        .unwrap();
+
+                    short.quantity -= covered;
+                    coverable_shares -= covered * short.multiplier;
+                }
+            }
+
+            for short in shorts.iter_mut() {
+                while short.quantity > 0 {
+                    let best = longs
+                        .iter()
+                        .enumerate()
+                        .filter(|&(_, l)| l.quantity > 0)
+                        .filter(|&(_, l)| {
+                            l.expiration_date.num_days_from_ce()
+                                >= short.expiration_date.num_days_from_ce()
+                        })
+                        .min_by_key(|&(_, l)| spread_width(*kind, short, l))
+                        .map(|(i, _)| i);
+
+                    let long = match best {
+                        Some(i) => &mut longs[i],
+                        None => break,
+                    };
+
+                    let pairs = *[short.quantity, long.quantity].iter().min().unwrap();
+
+                    // what the pair is worth now less the least it can be
+                    // worth at expiration
+                    let width = spread_width(*kind, short, long);
+                    let worst_case = if width > Money::zero() {
+                        width
+                    } else {
+                        Money::zero()
+                    };
+
+                    let per_pair = long.price * long.multiplier - short.price * short.multiplier
+                        + worst_case * short.multiplier;
+                    let per_pair = if per_pair > Money::zero() {
+                        per_pair
+                    } else {
+                        Money::zero()
+                    };
+
+                    requirement = requirement + same(per_pair * pairs);
+
+                    short.quantity -= pairs;
+                    long.quantity -= pairs;
+                }
+
+                if short.quantity > 0 {
+                    let underlying = holdings[0].underlying_price();
+                    let per_contract = self.naked_requirement(*kind, short, underlying);
+
+                    requirement = requirement + same(per_contract * short.quantity);
+                }
+            }
+
+            for long in longs.iter().filter(|l| l.quantity > 0) {
+                requirement = requirement + same(long.price * long.multiplier * long.quantity);
+            }
+        }
+
+        requirement
+    }
+
+    fn stock_requirement(&self, stock: &Holding) -> MarginRequirement {
+        let value = stock.price() * stock.quantity().abs();
+
+        if stock.quantity() > 0 {
+            MarginRequirement::new(value * 50 / 100, value * 25 / 100)
+        } else {
+            MarginRequirement::new(value * 50 / 100, value * 30 / 100)
+        }
+    }
+
+    fn naked_requirement(&self, kind: HoldingKind, short: &Leg, underlying: Money) -> Money {
+        let out_of_the_money = match kind {
+            HoldingKind::Call => short.strike_price - underlying,
+            _ => underlying - short.strike_price,
+        };
+
+        let out_of_the_money = if out_of_the_money > Money::zero() {
+            out_of_the_money
+        } else {
+            Money::zero()
+        };
+
+        let minimum = match kind {
+            HoldingKind::Call => underlying * 10 / 100,
+            _ => short.strike_price * 10 / 100,
+        };
+
+        let standard = underlying * 20 / 100 - out_of_the_money;
+
+        let per_share = if standard > minimum {
+            standard
+        } else {
+            minimum
+        };
+
+        per_share * short.multiplier
+    }
+}
+
+impl MarginCalculator for RegT {
+    fn name(&self) -> &'static str {
+        "Reg-T"
+    }
+
+    fn requirement_for(&self, holdings: &[Holding]) -> MarginRequirement {
+        let mut symbols: Vec<Rc<str>> = holdings.iter().map(|h| h.symbol()).collect();
+        symbols.sort();
+        symbols.dedup();
+
+        symbols
+            .iter()
+            .map(|s| {
+                let hs: Vec<&Holding> = holdings.iter().filter(|h| h.symbol() == *s).collect();
+                self.requirement_for_underlying(&hs)
+            })
+            .fold(MarginRequirement::zero(), |sum, r| sum + r)
+    }
+}
+
+fn same(amount: Money) -> MarginRequirement {
+    MarginRequirement::new(amount, amount)
+}
+
+// (shorts, longs) for options of `kind`
+fn legs_for(holdings: &[&Holding], kind: HoldingKind) -> (Vec<Leg>, Vec<Leg>) {
+    let mut shorts = vec![];
+    let mut longs = vec![];
+
+    for h in holdings.iter().filter(|h| h.kind() == kind && h.quantity() != 0) {
+        let leg = Leg {
+            strike_price: h.strike_price(),
+            expiration_date: h.expiration_date(),
+            quantity: h.quantity().abs(),
+            price: h.price(),
+            multiplier: h.multiplier(),
+        };
+
+        if h.quantity() < 0 {
+            shorts.push(leg);
+        } else {
+            longs.push(leg);
+        }
+    }
+
+    (shorts, longs)
+}
+
+// how much the short's strike is better than the long's per share.  when this
+// is positive the pair can be worth as little as -width at expiration, when
+// it's zero or negative the pair can't be worth less than nothing.
+fn spread_width(kind: HoldingKind, short: &Leg, long: &Leg) -> Money {
+    match kind {
+        HoldingKind::Call => long.strike_price - short.strike_price,
+        _ => short.strike_price - long.strike_price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::quote::Quote;
+    use assassin::quote_builder::QuoteBuilder;
+
+    fn quote(call: bool, strike: i32, expiration_month: u32, price: Money) -> Quote {
+        QuoteBuilder::new()
+            .expiring(2013, expiration_month, 21)
+            .with_call(call)
+            .with_strike(strike)
+            .with_price(price)
+            .build()
+    }
+
+    // the quotes have no spread, so any mark is their price
+    fn option(quote: Quote, quantity: i32, multiplier: i32) -> Holding {
+        Holding::option(&quote, quantity, quote.bid(), multiplier)
+    }
+
+    fn initial_for(holdings: Vec<Holding>) -> Money {
+        RegT::new().requirement_for(&holdings).initial()
+    }
+
+    #[test]
+    fn test_naked_options() {
+        // 20% of $100 less $5 OTM = $15 vs 10% of $100
+        let call = option(quote(true, 105, 2, Money::new(1, 0)), -2, 100);
+        assert!(initial_for(vec![call]) == Money::new(3_000, 0));
+
+        // 20% of $100 less $30 OTM is under the 10% of strike ($7) minimum
+        let put = option(quote(false, 70, 2, Money::new(0, 10)), -1, 100);
+        assert!(initial_for(vec![put]) == Money::new(700, 0));
+
+        // a 3:2 split left the contracts delivering 150 shares
+        let call = option(quote(true, 105, 2, Money::new(1, 0)), -2, 150);
+        assert!(initial_for(vec![call]) == Money::new(4_500, 0));
+    }
+
+    #[test]
+    fn test_long_option_and_stock() {
+        let call = option(quote(true, 95, 2, Money::new(6, 0)), 1, 100);
+        assert!(initial_for(vec![call]) == Money::new(600, 0));
+
+        let stock = Holding::stock(Rc::from("AAPL"), 100, Money::new(100, 0), Utc::now());
+        let requirement = RegT::new().requirement_for(&[stock]);
+        assert!(requirement.initial() == Money::new(5_000, 0));
+        assert!(requirement.maintenance() == Money::new(2_500, 0));
+    }
+
+    #[test]
+    fn test_covered_call() {
+        let stock = Holding::stock(Rc::from("AAPL"), 100, Money::new(100, 0), Utc::now());
+        let call = option(quote(true, 105, 2, Money::new(1, 0)), -1, 100);
+
+        assert!(initial_for(vec![stock, call]) == Money::new(5_000, 0));
+    }
+
+    #[test]
+    fn test_spreads() {
+        // bear call spread: $5 wide for a $2 credit risks $3
+        let short = option(quote(true, 100, 2, Money::new(4, 0)), -1, 100);
+        let long = option(quote(true, 105, 2, Money::new(2, 0)), 1, 100);
+        assert!(initial_for(vec![short, long]) == Money::new(300, 0));
+
+        // PMCC: deep long call further out only requires the net debit
+        let short = option(quote(true, 105, 2, Money::new(2, 0)), -1, 100);
+        let long = option(quote(true, 80, 6, Money::new(22, 0)), 1, 100);
+        assert!(initial_for(vec![short, long]) == Money::new(2_000, 0));
+
+        // a long that expires first doesn't cover the short
+        let short = option(quote(true, 105, 6, Money::new(4, 0)), -1, 100);
+        let long = option(quote(true, 80, 2, Money::new(21, 0)), 1, 100);
+        assert!(initial_for(vec![short, long]) == Money::new(1_500 + 2_100, 0));
+    }
+}
@@ -0,0 +1,82 @@
+use std::ops::Add;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// How much of the account's equity has to be put up for its holdings: initial
+// when opening new positions and maintenance to keep holding them.
+#[derive(Clone, Copy)]
+pub struct MarginRequirement {
+    initial: Money,
+    maintenance: Money,
+}
+
+impl MarginRequirement {
+    pub fn new(initial: Money, maintenance: Money) -> MarginRequirement {
+        MarginRequirement {
+            initial: initial,
+            maintenance: maintenance,
+        }
+    }
+
+    pub fn zero() -> MarginRequirement {
+        MarginRequirement::new(Money::zero(), Money::zero())
+    }
+
+    pub fn initial(&self) -> Money {
+        self.initial
+    }
+
+    pub fn maintenance(&self) -> Money {
+        self.maintenance
+    }
+}
+
+impl Add for MarginRequirement {
+    type Output = MarginRequirement;
+
+    fn add(self, rhs: MarginRequirement) -> MarginRequirement {
+        MarginRequirement::new(
+            self.initial + rhs.initial,
+            self.maintenance + rhs.maintenance,
+        )
+    }
+}
+
+// Raised at the end of a day when equity has fallen below the maintenance
+// requirement.
+#[derive(Clone)]
+pub struct MarginCall {
+    date: DateTime<Utc>,
+    equity: Money,
+    requirement: Money,
+}
+
+impl MarginCall {
+    pub fn new(date: DateTime<Utc>, equity: Money, requirement: Money) -> MarginCall {
+        MarginCall {
+            date: date,
+            equity: equity,
+            requirement: requirement,
+        }
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    pub fn equity(&self) -> Money {
+        self.equity
+    }
+
+    pub fn requirement(&self) -> Money {
+        self.requirement
+    }
+
+    pub fn deficit(&self) -> Money {
+        self.requirement - self.equity
+    }
+}
@@ -0,0 +1,95 @@
+use assassin::json::Json;
+use assassin::stats;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// How much of the account a margin calculator tied up over a run.  The
+// Broker keeps one for the calculator it enforces and one for every
+// comparison calculator, so the same trades can be compared under each
+// regime.
+#[derive(Clone)]
+pub struct MarginUsage {
+    calculator: &'static str,
+    enforced: bool,
+    highest_requirement: Money,
+    lowest_buying_power: Option<Money>,
+}
+
+impl MarginUsage {
+    pub fn new(calculator: &'static str, enforced: bool) -> MarginUsage {
+        MarginUsage {
+            calculator: calculator,
+            enforced: enforced,
+            highest_requirement: Money::zero(),
+            lowest_buying_power: None,
+        }
+    }
+
+    // the initial requirement against the account's equity at the end of a day
+    pub fn record(&mut self, equity: Money, requirement: Money) {
+        let buying_power = equity - requirement;
+
+        if requirement > self.highest_requirement {
+            self.highest_requirement = requirement;
+        }
+
+        self.lowest_buying_power = match self.lowest_buying_power {
+            Some(b) if b <= buying_power => Some(b),
+            _ => Some(buying_power),
+        };
+    }
+
+    pub fn calculator(&self) -> &'static str {
+        self.calculator
+    }
+
+    pub fn is_enforced(&self) -> bool {
+        self.enforced
+    }
+
+    pub fn highest_requirement(&self) -> Money {
+        self.highest_requirement
+    }
+
+    // zero if nothing was recorded
+    pub fn lowest_buying_power(&self) -> Money {
+        self.lowest_buying_power.unwrap_or(Money::zero())
+    }
+
+    // the profit over the most capital the regime ever tied up, i.e., the
+    // return on what it took to run the strategy under it.  0.1 is 10%.
+    pub fn return_on_requirement(&self, profit: Money) -> f64 {
+        stats::ratio(profit.raw_value() as f64, self.highest_requirement.raw_value() as f64)
+    }
+
+    pub fn to_json(&self, profit: Money) -> Json {
+        Json::Object(vec![
+            ("calculator", Json::string(self.calculator)),
+            ("enforced", Json::Bool(self.enforced)),
+            ("highest_requirement", Json::Money(self.highest_requirement)),
+            ("lowest_buying_power", Json::Money(self.lowest_buying_power())),
+            ("return_on_requirement", Json::Number(self.return_on_requirement(profit))),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut usage = MarginUsage::new("Portfolio", false);
+        assert!(usage.lowest_buying_power() == Money::zero());
+
+        usage.record(Money::new(100_000, 0), Money::new(20_000, 0));
+        usage.record(Money::new(90_000, 0), Money::new(25_000, 0));
+        usage.record(Money::new(110_000, 0), Money::new(5_000, 0));
+
+        assert!(usage.highest_requirement() == Money::new(25_000, 0));
+        assert!(usage.lowest_buying_power() == Money::new(65_000, 0));
+        assert_eq!(usage.return_on_requirement(Money::new(10_000, 0)), 0.4);
+    }
+}
@@ -0,0 +1,39 @@
+use assassin::quote::Quote;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// How open positions are valued.
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarkMethod {
+    // halfway between the bid and the ask
+    Mid,
+    // what closing the position would get right now: the bid for longs and
+    // the ask for shorts
+    BidAsk,
+    // the last traded price
+    Last,
+}
+
+impl MarkMethod {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MarkMethod::Mid => "mid",
+            MarkMethod::BidAsk => "bid/ask",
+            MarkMethod::Last => "last",
+        }
+    }
+
+    pub fn price(&self, quote: &Quote, long: bool) -> Money {
+        match *self {
+            MarkMethod::Mid => quote.midpoint_price(),
+            MarkMethod::BidAsk => if long {
+                quote.bid()
+            } else {
+                quote.ask()
+            },
+            MarkMethod::Last => quote.last_price(),
+        }
+    }
+}
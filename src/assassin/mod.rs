@@ -1,10 +1,47 @@
+pub mod assignment;
+pub mod backtest_result;
+pub mod benchmark;
 pub mod broker;
+pub mod circuit_breaker;
+pub mod combo_order;
 pub mod commission;
+pub mod conditional_order;
+pub mod corporate_action;
+pub mod csv;
+#[cfg(test)]
+pub mod equity_curve_builder;
+pub mod equity_point;
+pub mod exercise;
 pub mod filled_order;
+pub mod greeks;
+pub mod holding;
+pub mod interest;
+pub mod journal_entry;
+pub mod json;
+pub mod lot;
+pub mod margin;
+pub mod margin_requirement;
+pub mod margin_usage;
+pub mod mark_method;
 pub mod order;
+pub mod order_request;
+pub mod pnl_snapshot;
 pub mod position;
+pub mod pricing;
 pub mod quote;
+#[cfg(test)]
+pub mod quote_builder;
+pub mod returns_table;
+pub mod risk_limits;
 pub mod simulation;
+pub mod stats;
+pub mod stock_position;
+pub mod submitted_order;
+pub mod targets;
+pub mod tear_sheet;
+pub mod trade;
+pub mod trade_group;
+pub mod trade_stats;
 
 pub mod feeds;
 pub mod models;
@@ -1,5 +1,5 @@
 use assassin::broker::Broker;
-use assassin::order::Order;
+use assassin::order_request::OrderRequest;
 use assassin::traits::*;
 
 #[allow(dead_code)]
@@ -19,7 +19,7 @@ impl Model for DummyModel {
 
     fn before_simulation(&mut self, _b: &Broker) {}
     fn after_simulation(&mut self, _b: &Broker) {}
-    fn run_logic(&mut self, _b: &Broker) -> Vec<Order> {
+    fn run_logic(&mut self, _b: &Broker) -> Vec<OrderRequest> {
         vec![]
     }
     fn show_bod_header(&self, _b: &Broker) {}
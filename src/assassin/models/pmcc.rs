@@ -1,7 +1,11 @@
 use assassin::broker::Broker;
+use assassin::combo_order::ComboOrder;
+use assassin::exercise::{ExerciseEvent, ExerciseKind};
 use assassin::order::Order;
+use assassin::order_request::OrderRequest;
 use assassin::position::Position;
 use assassin::quote::Quote;
+use assassin::trade_group::TradeGroup;
 use assassin::traits::*;
 
 extern crate chrono;
@@ -105,22 +109,44 @@ static NUM_CONTRACTS: i32 = 5;
 static STRIKES_ABOVE: i32 = 2;
 static STRIKES_BELOW: i32 = 4;
 
-pub struct PMCC {}
+// Each diagonal is its own TradeGroup, so several instances (with different
+// names) can run against the same broker without mixing up their legs.
+pub struct PMCC {
+    strategy: String,
+    next_trade_id: u32,
+}
 
 impl PMCC {
     pub fn new() -> PMCC {
-        PMCC {}
+        PMCC {
+            strategy: String::from("PMCC"),
+            next_trade_id: 1,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_name(mut self, name: &str) -> PMCC {
+        self.strategy = String::from(name);
+
+        self
+    }
+
+    fn new_trade_group(&mut self) -> TradeGroup {
+        let group = TradeGroup::new(&self.strategy, self.next_trade_id);
+        self.next_trade_id += 1;
+
+        group
     }
 
     // --------------------------------------------------------------------------------------------
 
-    fn look_for_new_short_position_to_open(&self, broker: &Broker) -> Option<Order> {
+    fn find_short_call<'a>(&self, broker: &'a Broker) -> Option<&'a Quote> {
         let underlying_price = broker.underlying_price_for(TICKER);
         let date = broker.current_date();
 
         debug!(
-            "** Searching for candidate quote for upper call ({} strikes below)",
-            STRIKES_BELOW
+            "** Searching for candidate quote for upper call ({} strikes above)",
+            STRIKES_ABOVE
         );
 
         let quotes: Vec<&Quote> = broker
@@ -131,24 +157,20 @@ impl PMCC {
 
         print_chain(quotes.clone(), date);
 
-        let quote = match n_strikes_above(quotes.clone(), STRIKES_ABOVE, underlying_price) {
+        match n_strikes_above(quotes, STRIKES_ABOVE, underlying_price) {
             Some(quote) => {
                 debug!("** Found candidate:");
                 print_quote(quote, date);
-                quote
+                Some(quote)
             }
             None => {
                 debug!("!! No quote found");
-                return None;
+                None
             }
-        };
-
-        let o = Order::new_sell_open_order(quote, NUM_CONTRACTS, quote.midpoint_price());
-
-        Some(o)
+        }
     }
 
-    fn look_for_new_long_position_to_open(&self, broker: &Broker) -> Option<Order> {
+    fn find_long_call<'a>(&self, broker: &'a Broker) -> Option<&'a Quote> {
         let underlying_price = broker.underlying_price_for(TICKER);
         let date = broker.current_date();
 
@@ -165,25 +187,70 @@ impl PMCC {
 
         print_chain(quotes.clone(), date);
 
-        let quote = match n_strikes_below(quotes, STRIKES_BELOW, underlying_price) {
+        match n_strikes_below(quotes, STRIKES_BELOW, underlying_price) {
             Some(quote) => {
                 debug!("** Found candidate:");
                 print_quote(quote, date);
-                quote
+                Some(quote)
             }
             None => {
                 debug!("!! No quote found");
-                return None;
+                None
             }
-        };
+        }
+    }
 
-        let o = Order::new_buy_open_order(quote, NUM_CONTRACTS, quote.midpoint_price());
+    fn look_for_new_short_position_to_open(
+        &self,
+        broker: &Broker,
+        group: &TradeGroup,
+    ) -> Option<Order> {
+        self.find_short_call(broker).map(|q| {
+            Order::new_sell_open_order(q, NUM_CONTRACTS, q.midpoint_price())
+                .with_group(group.clone())
+        })
+    }
 
-        Some(o)
+    fn look_for_new_long_position_to_open(
+        &self,
+        broker: &Broker,
+        group: &TradeGroup,
+    ) -> Option<Order> {
+        self.find_long_call(broker).map(|q| {
+            Order::new_buy_open_order(q, NUM_CONTRACTS, q.midpoint_price())
+                .with_group(group.clone())
+        })
     }
 
+    // opens both legs of the diagonal as a single ticket so we never end up
+    // holding only one side of it
+    fn look_for_new_diagonal_to_open(&mut self, broker: &Broker) -> Option<ComboOrder> {
+        let short = self.find_short_call(broker)?;
+        let long = self.find_long_call(broker)?;
+
+        // the combo only fills if the limit covers the natural market
+        let net_debit = long.ask() - short.bid();
+
+        if net_debit < Money::zero() {
+            debug!("!! diagonal would be a credit ({}), skipping", net_debit);
+            return None;
+        }
 
-    fn manage_positions(&self, _broker: &Broker, _positions: Vec<&Position>) -> Vec<Order> {
+        let combo = ComboOrder::new(NUM_CONTRACTS)
+            .buy_to_open(long, 1)
+            .sell_to_open(short, 1)
+            .with_debit_limit(net_debit)
+            .with_group(self.new_trade_group());
+
+        Some(combo)
+    }
+
+    fn manage_positions(
+        &self,
+        _broker: &Broker,
+        _group: &TradeGroup,
+        _positions: Vec<&Position>,
+    ) -> Vec<OrderRequest> {
         vec![]
     }
 }
@@ -195,55 +262,64 @@ impl Model for PMCC {
 
     fn before_simulation(&mut self, _broker: &Broker) {}
 
-    fn run_logic(&mut self, broker: &Broker) -> Vec<Order> {
-        let positions = broker.open_positions();
+    fn run_logic(&mut self, broker: &Broker) -> Vec<OrderRequest> {
+        let groups = broker.open_trade_groups(&self.strategy);
 
         let mut orders = vec![];
 
-        match positions.len() {
-            2 => {
-                debug!("** Managing existing positions");
-                orders = self.manage_positions(broker, positions);
+        if groups.is_empty() {
+            debug!("** Looking for new positions to open");
+            match self.look_for_new_diagonal_to_open(broker) {
+                Some(combo) => orders.push(combo.into()),
+                None => debug!("didn't find a candidate for both positions"),
             }
-            1 => if positions[0].is_long() {
-                debug!("** Opening new short position");
-                if let Some(o) = self.look_for_new_short_position_to_open(broker) {
-                    orders.push(o);
-                }
-            } else {
-                if let Some(o) = self.look_for_new_long_position_to_open(broker) {
-                    orders.push(o);
-                }
-            },
-            0 => {
-                debug!("** Looking for new positions to open");
-                if let Some(o) = self.look_for_new_short_position_to_open(broker) {
-                    orders.push(o);
-                }
+        }
 
-                if let Some(o) = self.look_for_new_long_position_to_open(broker) {
-                    orders.push(o);
-                }
+        for group in groups {
+            let positions = broker.open_positions_in_group(&group);
 
-                // only process orders if we found candidates for both
-                if orders.len() != 2 {
-                    debug!("didn't find a candidate for both positions");
-                    orders.clear();
+            let has_long = positions.iter().any(|p| p.is_long());
+            let has_short = positions.iter().any(|p| p.is_short());
+
+            if has_long && has_short {
+                debug!("** Managing existing positions of {}", group);
+                orders.extend(self.manage_positions(broker, &group, positions));
+            } else if has_long {
+                debug!("** Opening new short position for {}", group);
+                if let Some(o) = self.look_for_new_short_position_to_open(broker, &group) {
+                    orders.push(o.into());
+                }
+            } else if has_short {
+                debug!("** Opening new long position for {}", group);
+                if let Some(o) = self.look_for_new_long_position_to_open(broker, &group) {
+                    orders.push(o.into());
                 }
             }
-            _ => panic!("unexpected number of positions: {}", positions.len()),
-        };
+        }
 
         orders
     }
 
+    fn handle_exercise_event(&mut self, _broker: &Broker, event: &ExerciseEvent) {
+        match event.kind() {
+            ExerciseKind::EarlyAssigned => {
+                match event.group() {
+                    Some(g) => info!("!! Short call of {} assigned early: {}", g, event.summary()),
+                    None => info!("!! Short call assigned early: {}", event.summary()),
+                }
+            }
+            _ => debug!("** {}", event.summary()),
+        }
+    }
+
     fn after_simulation(&mut self, _broker: &Broker) {}
 
     fn show_bod_header(&self, broker: &Broker) {
         info!(
-            "===== start of {} ======= Balance: {} =================",
+            "===== start of {} ======= Balance: {} ======= Buying power: {} =====",
             broker.current_date(),
             broker.account_balance(),
+            broker.buying_power(),
         );
         info!("");
     }
@@ -1,6 +1,7 @@
 use std::rc::Rc;
 
 use assassin::quote::Quote;
+use assassin::trade_group::TradeGroup;
 
 extern crate greenback;
 use greenback::Greenback as Money;
@@ -14,6 +15,9 @@ pub struct Order {
     quantity: i32,
     limit: Money,
     strike_price: Money,
+    // lots a closing order should close when lots are matched by SpecificLot
+    lot_ids: Vec<u32>,
+    group: Option<TradeGroup>,
     // date: DateTime<Utc>, // TODO: flesh this out (Date order placed... could be GTC, etc.)
 }
 
@@ -35,7 +39,6 @@ impl Order {
     }
 
     // "AAPL: BUY 10 CALL $150 STRIKE at LIMIT $2.50"
-    #[allow(dead_code)]
     pub fn summary(&self) -> String {
         format!(
             "{} {} {} {} STRIKE at LIMIT {}",
@@ -74,6 +77,8 @@ impl Order {
             quantity: quantity,
             limit: limit,
             strike_price: quote.strike_price(),
+            lot_ids: vec![],
+            group: None,
         }
     }
 
@@ -100,6 +105,28 @@ impl Order {
         o
     }
 
+    #[allow(dead_code)]
+    pub fn with_lot_ids(mut self, lot_ids: Vec<u32>) -> Order {
+        self.lot_ids = lot_ids;
+
+        self
+    }
+
+    pub fn lot_ids(&self) -> &Vec<u32> {
+        &self.lot_ids
+    }
+
+    // orders that close a grouped position need the same group
+    pub fn with_group(mut self, group: TradeGroup) -> Order {
+        self.group = Some(group);
+
+        self
+    }
+
+    pub fn group(&self) -> Option<TradeGroup> {
+        self.group.clone()
+    }
+
     pub fn buy_to_open(&self) -> bool {
         self.buy && self.open
     }
@@ -156,28 +183,21 @@ mod tests {
 
     use super::*;
 
+    use assassin::filled_order::FilledOrder;
+    use assassin::quote_builder::QuoteBuilder;
+
+    extern crate chrono;
+    use self::chrono::prelude::*;
+
     fn dummy_quote(bid: Money, ask: Money) -> Quote {
-        Quote::new(
-            "symbol".to_string(), // symbol
-            Utc::now(),           // expiration date
-            ask,                  // ask
-            bid,                  // bid
-            Money::new(1, 0),     // last_price
-            true,                 // call
-            Money::new(100, 0),   // strike_price
-            0,                    // volume
-            0.0,                  // IV
-            0.0,                  // delta
-            0.0,                  // gamma
-            0.0,                  // vega
-            0,                    // open interest
-            Money::new(101, 0),   // underlying
-            Utc::now(),           // date (of quote)
-        )
+        QuoteBuilder::new()
+            .with_bid_ask(bid, ask)
+            .with_underlying(Money::new(101, 0))
+            .build()
     }
 
-    fn filled_order(quote: &Quote) -> Order {
-        Order {
+    fn filled_order(quote: &Quote) -> FilledOrder {
+        let order = Order {
             symbol: quote.symbol(),
             name: quote.name(),
             buy: true,
@@ -185,12 +205,11 @@ mod tests {
             quantity: 10,
             limit: Money::new(1, 0),
             strike_price: Money::new(1, 0),
-            quote: Some(quote.clone()),
-            fill_price: Some(quote.ask()),
-            commission: Some(Money::zero()),
-            filled_date: Some(Utc::now()),
-            closed_by_broker: false,
-        }
+            lot_ids: vec![],
+            group: None,
+        };
+
+        FilledOrder::new(order, quote, quote.ask(), Utc::now())
     }
 
     #[test]
@@ -0,0 +1,32 @@
+use assassin::combo_order::ComboOrder;
+use assassin::conditional_order::ConditionalOrder;
+use assassin::order::Order;
+
+// Everything a Model can ask the Broker to do in a single run_logic() call.
+pub enum OrderRequest {
+    Single(Order),
+    Combo(ComboOrder),
+    Conditional(ConditionalOrder),
+    // when one of these fills, the rest are cancelled (e.g., a bracket of a
+    // stop loss and a take profit on the same position)
+    #[allow(dead_code)]
+    OneCancelsOther(Vec<ConditionalOrder>),
+}
+
+impl From<Order> for OrderRequest {
+    fn from(order: Order) -> OrderRequest {
+        OrderRequest::Single(order)
+    }
+}
+
+impl From<ComboOrder> for OrderRequest {
+    fn from(combo: ComboOrder) -> OrderRequest {
+        OrderRequest::Combo(combo)
+    }
+}
+
+impl From<ConditionalOrder> for OrderRequest {
+    fn from(order: ConditionalOrder) -> OrderRequest {
+        OrderRequest::Conditional(order)
+    }
+}
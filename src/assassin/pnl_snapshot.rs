@@ -0,0 +1,42 @@
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Profit and loss at the close of a trading day, for a position or the whole
+// account, from the start of the position (or simulation) until then.
+#[derive(Clone)]
+pub struct PnlSnapshot {
+    date: DateTime<Utc>,
+    realized: Money,
+    unrealized: Money,
+}
+
+impl PnlSnapshot {
+    pub fn new(date: DateTime<Utc>, realized: Money, unrealized: Money) -> PnlSnapshot {
+        PnlSnapshot {
+            date: date,
+            realized: realized,
+            unrealized: unrealized,
+        }
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    #[allow(dead_code)]
+    pub fn realized(&self) -> Money {
+        self.realized
+    }
+
+    #[allow(dead_code)]
+    pub fn unrealized(&self) -> Money {
+        self.unrealized
+    }
+
+    pub fn total(&self) -> Money {
+        self.realized + self.unrealized
+    }
+}
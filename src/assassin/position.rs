@@ -1,7 +1,12 @@
 use std::rc::Rc;
 
 use assassin::filled_order::FilledOrder;
-use assassin::quote::Quote;
+use assassin::csv::{decimal, quoted};
+use assassin::json::Json;
+use assassin::lot::{ClosedLot, Lot, LotMatching};
+use assassin::mark_method::MarkMethod;
+use assassin::quote::{self, Quote};
+use assassin::trade_group::TradeGroup;
 
 extern crate chrono;
 use self::chrono::prelude::*;
@@ -9,13 +14,30 @@ use self::chrono::prelude::*;
 extern crate greenback;
 use greenback::Greenback as Money;
 
+// positions are tracked per trade group (if any) and option
+pub type PositionKey = (Option<TradeGroup>, Rc<str>);
+
 #[derive(Clone)]
 pub struct Position {
     name: Rc<str>,
+    group: Option<TradeGroup>,
     symbol: Rc<str>,
     quantity: i32,
     expiration_date: DateTime<Utc>,
+    call: bool,
+    strike_price: Money,
+    // shares delivered per contract, which only changes with corporate actions
+    multiplier: i32,
     orders: Vec<Rc<FilledOrder>>, // TODO: rename to filled_orders
+    // in the order they were opened
+    lots: Vec<Lot>,
+    closed_lots: Vec<ClosedLot>,
+    lot_count: u32,
+    lot_matching: LotMatching,
+    // (orders applied before it, new contracts per old one) for every
+    // whole-number split, since the quantities of the orders before one are
+    // in old contracts
+    contract_splits: Vec<(usize, i32)>,
 }
 
 impl Position {
@@ -24,15 +46,36 @@ impl Position {
     pub fn new(quote: &Quote) -> Position {
         Position {
             name: quote.name(),
+            group: None,
             symbol: quote.symbol(),
             quantity: 0,
             expiration_date: quote.expiration_date(),
+            call: quote.is_call(),
+            strike_price: quote.strike_price(),
+            multiplier: 100,
             // don't set the order here because it gets applied in
             // apply_order() below.
             orders: vec![],
+            lots: vec![],
+            closed_lots: vec![],
+            lot_count: 0,
+            lot_matching: LotMatching::Fifo,
+            contract_splits: vec![],
         }
     }
 
+    pub fn with_group(mut self, group: Option<TradeGroup>) -> Position {
+        self.group = group;
+
+        self
+    }
+
+    pub fn with_lot_matching(mut self, lot_matching: LotMatching) -> Position {
+        self.lot_matching = lot_matching;
+
+        self
+    }
+
     pub fn broker_closed_order_count(&self) -> i32 {
         self.orders
             .iter()
@@ -41,19 +84,19 @@ impl Position {
             .len() as i32
     }
 
-    // OPTIMIZE: this can be updated when orders are applied
+    // only counts contracts that have been closed (before commission).
+    // contracts that are still open show up in unrealized_profit().
     pub fn realized_profit(&self) -> Money {
-        self.orders.iter().fold(Money::zero(), |sum, o|
-            // NOTE: a buy order really changes the position's value
-            //       by a negative amount because it's tying up capital
-            //       in a debit. a sell order grants a credit and is thus
-            //       a positive value.
-            //
-            //       canonical_quantity() returns the correct values
-            //       (i.e., a buy is 10, a sell is -10) for quantity, but
-            //       we want to invert this because we want a buy to be
-            //       a debit and a sell to be a credit.
-            sum - (o.fill_price() * 100 * o.canonical_quantity()))
+        self.closed_lots.iter().map(|l| l.realized_profit()).sum()
+    }
+
+    // what was paid (> 0) or received (< 0) for the contracts still open
+    pub fn cost_basis(&self) -> Money {
+        self.lots.iter().map(|l| l.cost_basis()).sum()
+    }
+
+    pub fn unrealized_profit(&self, current_quote: &Quote, mark: MarkMethod) -> Money {
+        self.market_value(current_quote, mark) - self.cost_basis()
     }
 
     // OPTIMIZE: this can be updated when orders are applied
@@ -69,6 +112,18 @@ impl Position {
         Rc::clone(&self.name)
     }
 
+    pub fn group(&self) -> Option<TradeGroup> {
+        self.group.clone()
+    }
+
+    pub fn key(&self) -> PositionKey {
+        (self.group(), self.name())
+    }
+
+    pub fn is_in_group(&self, group: &TradeGroup) -> bool {
+        self.group.as_ref() == Some(group)
+    }
+
     pub fn orders(&self) -> &Vec<Rc<FilledOrder>> {
         &self.orders
     }
@@ -79,9 +134,96 @@ impl Position {
 
     pub fn apply_order(&mut self, order: Rc<FilledOrder>) {
         self.quantity += order.canonical_quantity();
+        self.match_lots(&order);
         self.orders.push(order);
     }
 
+    // ===== lots =======================================================================
+
+    // closes lots on the other side of the fill and opens a new lot with
+    // whatever is left over
+    fn match_lots(&mut self, order: &FilledOrder) {
+        let direction = order.canonical_quantity().signum();
+        let mut remaining = order.quantity();
+
+        while remaining > 0 {
+            let i = match self.next_lot_to_close(direction, order.lot_ids()) {
+                Some(i) => i,
+                None => break,
+            };
+
+            let contracts = *[remaining, self.lots[i].quantity().abs()]
+                .iter()
+                .min()
+                .unwrap();
+
+            let closed_lot = self.lots[i].close(contracts, order.fill_price(), order.fill_date());
+            self.closed_lots.push(closed_lot);
+
+            if self.lots[i].quantity() == 0 {
+                self.lots.remove(i);
+            }
+
+            remaining -= contracts;
+        }
+
+        if remaining > 0 {
+            self.lot_count += 1;
+
+            self.lots.push(Lot::new(
+                self.lot_count,
+                order.fill_date(),
+                remaining * direction,
+                order.fill_price(),
+                order.multiplier(),
+            ));
+        }
+    }
+
+    fn next_lot_to_close(&self, direction: i32, lot_ids: &[u32]) -> Option<usize> {
+        // open lots are always on the same side, and there's nothing to close
+        // if it's the fill's side
+        if self.lots.is_empty() || self.lots[0].quantity().signum() == direction {
+            return None;
+        }
+
+        if self.lot_matching == LotMatching::SpecificLot {
+            for id in lot_ids {
+                if let Some(i) = self.lots.iter().position(|l| l.id() == *id) {
+                    return Some(i);
+                }
+            }
+        }
+
+        match self.lot_matching {
+            LotMatching::Lifo => Some(self.lots.len() - 1),
+            _ => Some(0),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn lots(&self) -> &Vec<Lot> {
+        &self.lots
+    }
+
+    pub fn closed_lots(&self) -> &Vec<ClosedLot> {
+        &self.closed_lots
+    }
+
+    // per share across the open lots
+    #[allow(dead_code)]
+    pub fn average_cost(&self) -> Money {
+        let contracts: i32 = self.lots.iter().map(|l| l.quantity().abs()).sum();
+
+        if contracts == 0 {
+            return Money::zero();
+        }
+
+        let total: Money = self.lots.iter().map(|l| l.price() * l.quantity().abs()).sum();
+
+        total / contracts
+    }
+
     pub fn quantity(&self) -> i32 {
         self.quantity
     }
@@ -90,11 +232,68 @@ impl Position {
         self.expiration_date
     }
 
+    pub fn is_call(&self) -> bool {
+        self.call
+    }
+
+    pub fn strike_price(&self) -> Money {
+        self.strike_price
+    }
+
+    pub fn multiplier(&self) -> i32 {
+        self.multiplier
+    }
+
+    pub fn contract_splits(&self) -> &Vec<(usize, i32)> {
+        &self.contract_splits
+    }
+
+    // ===== corporate actions ==========================================================
+
+    // `to` new shares for every `from` old ones.  whole-number splits (2:1,
+    // 7:1) multiply the contracts and divide the strike so the position lines
+    // up with the standard contracts listed after the split.  anything else
+    // keeps the contracts and changes how many shares each one delivers.
+    pub fn apply_split(&mut self, to: i32, from: i32) {
+        if to % from == 0 {
+            self.quantity *= to / from;
+            self.strike_price /= to / from;
+            self.contract_splits.push((self.orders.len(), to / from));
+        } else {
+            self.strike_price = self.strike_price * from / to;
+            self.multiplier = self.multiplier * to / from;
+        }
+
+        for lot in self.lots.iter_mut() {
+            lot.apply_split(to, from);
+        }
+
+        self.rename();
+    }
+
+    // special dividends are passed on to option holders by lowering the strike
+    pub fn reduce_strike_price(&mut self, amount: Money) {
+        self.strike_price -= amount;
+        self.rename();
+    }
+
+    // keep the name in sync with the series' quotes after an adjustment
+    fn rename(&mut self) {
+        let name = quote::option_name(
+            &self.symbol,
+            self.expiration_date,
+            self.call,
+            self.strike_price,
+        );
+        let name_ref: &str = &name;
+
+        self.name = Rc::from(name_ref);
+    }
+
     pub fn is_long(&self) -> bool {
         self.quantity > 0
     }
 
-    #[allow(dead_code)]
     pub fn is_short(&self) -> bool {
         !self.is_long()
     }
@@ -104,25 +303,10 @@ impl Position {
         self.quantity == 0
     }
 
+    // counting contracts instead of opening vs closing orders keeps this right
+    // when a position is closed in pieces or its contracts were split
     pub fn is_open(&self) -> bool {
-        // TODO: why is calling is_filled() crashing?
-        // let closed_count = self.orders.iter().map(|o| o.is_filled()).len();
-        // let open_count = self.orders.len() - closed_count;
-
-        // open_count != closed_count
-
-        let mut open = 0;
-        let mut closed = 0;
-
-        for o in self.orders.iter() {
-            if o.is_open() {
-                open += 1;
-            } else {
-                closed += 1;
-            }
-        }
-
-        open != closed
+        self.quantity != 0
     }
 
     #[allow(dead_code)]
@@ -140,20 +324,52 @@ impl Position {
         self.expiration_date.num_days_from_ce() < current_date.num_days_from_ce()
     }
 
+    // what closing the position at the quote would bring in (or cost)
     #[allow(dead_code)]
     pub fn current_value(&self, current_quote: &Quote) -> Money {
-        println!("==========================================================");
-        self.orders
-            .iter()
-            .map(|o| {
-                let x = o.unrealized_value(current_quote);
-                println!("x: {}", x);
-                // TODO: print order deets
-                // println!("{}", o.summary());
-                println!("");
-                x
-            })
-            .sum()
+        self.market_value(current_quote, MarkMethod::BidAsk)
+    }
+
+    pub fn market_value(&self, current_quote: &Quote, mark: MarkMethod) -> Money {
+        mark.price(current_quote, self.is_long()) * self.multiplier * self.quantity
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("name", Json::string(&self.name)),
+            ("group", Json::optional(self.group.as_ref(), |g| Json::String(g.to_string()))),
+            ("symbol", Json::string(&self.symbol)),
+            ("call", Json::Bool(self.call)),
+            ("strike_price", Json::Money(self.strike_price)),
+            ("expiration_date", Json::date(self.expiration_date)),
+            ("quantity", Json::number(self.quantity)),
+            ("realized_profit", Json::Money(self.realized_profit())),
+            ("commission", Json::Money(self.commission_paid())),
+            ("orders", Json::number(self.order_count())),
+            ("broker_closed_orders", Json::number(self.broker_closed_order_count())),
+        ])
+    }
+
+    pub fn csv_header() -> &'static str {
+        "name,group,symbol,call,strike_price,expiration_date,quantity,realized_profit,\
+         commission,orders,broker_closed_orders"
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            quoted(&self.name),
+            quoted(&self.group.as_ref().map(|g| g.to_string()).unwrap_or_default()),
+            self.symbol,
+            self.call,
+            decimal(self.strike_price),
+            self.expiration_date.format("%Y-%m-%d"),
+            self.quantity,
+            decimal(self.realized_profit()),
+            decimal(self.commission_paid()),
+            self.order_count(),
+            self.broker_closed_order_count(),
+        )
     }
 }
 
@@ -162,10 +378,126 @@ mod tests {
 
     use super::*;
 
+    use assassin::order::Order;
+    use assassin::quote_builder::QuoteBuilder;
+
+    fn dummy_quote(bid: Money, ask: Money) -> Quote {
+        QuoteBuilder::new()
+            .with_bid_ask(bid, ask)
+            .with_underlying(Money::new(101, 0))
+            .build()
+    }
+
     #[test]
     fn test_current_value() {
-        let foo = dummy_quote();
-        assert!(true == true);
+        let quote = dummy_quote(Money::new(1, 0), Money::new(1, 10));
+        let order = Order::new_sell_open_order(&quote, 2, quote.bid());
+        let mut position = Position::new(&quote);
+        position.apply_order(Rc::new(FilledOrder::new(order, &quote, quote.bid(), Utc::now())));
+
+        // a short position costs what we'd pay to buy it back
+        assert!(position.current_value(&quote) == Money::zero() - Money::new(220, 0));
+        assert!(!position.is_long());
+    }
+
+    #[test]
+    fn test_market_value() {
+        let quote = dummy_quote(Money::new(1, 0), Money::new(1, 10));
+        let order = Order::new_buy_open_order(&quote, 2, quote.ask());
+        let mut position = Position::new(&quote);
+        position.apply_order(Rc::new(FilledOrder::new(order, &quote, quote.ask(), Utc::now())));
+
+        // a long position is worth what we could sell it for
+        assert!(position.current_value(&quote) == Money::new(200, 0));
+        assert!(position.market_value(&quote, MarkMethod::Mid) == Money::new(210, 0));
+        assert!(position.is_open());
+    }
+
+    fn fill(quote: &Quote, order: Order, price: Money, day: u32) -> Rc<FilledOrder> {
+        Rc::new(FilledOrder::new(order, quote, price, Utc.ymd(2013, 1, day).and_hms(0, 0, 0)))
+    }
+
+    fn position_with_two_lots(lot_matching: LotMatching) -> (Position, Quote) {
+        let quote = dummy_quote(Money::new(1, 0), Money::new(1, 10));
+        let mut position = Position::new(&quote).with_lot_matching(lot_matching);
+
+        let buy = Order::new_buy_open_order(&quote, 2, quote.ask());
+        position.apply_order(fill(&quote, buy.clone(), Money::new(1, 0), 2));
+        position.apply_order(fill(&quote, buy, Money::new(2, 0), 3));
+
+        (position, quote)
+    }
+
+    #[test]
+    fn test_lot_matching() {
+        let (mut fifo, quote) = position_with_two_lots(LotMatching::Fifo);
+        let (mut lifo, _) = position_with_two_lots(LotMatching::Lifo);
+        let (mut specific, _) = position_with_two_lots(LotMatching::SpecificLot);
+
+        assert!(fifo.average_cost() == Money::new(1, 50));
+
+        let sell = Order::new_sell_close_order(&quote, 3, quote.bid());
+        fifo.apply_order(fill(&quote, sell.clone(), Money::new(3, 0), 4));
+        lifo.apply_order(fill(&quote, sell.clone(), Money::new(3, 0), 4));
+        specific.apply_order(fill(&quote, sell.with_lot_ids(vec![2]), Money::new(3, 0), 4));
+
+        // all of lot 1 and half of lot 2
+        assert_eq!(fifo.closed_lots().len(), 2);
+        assert_eq!(fifo.lots()[0].id(), 2);
+        assert!(fifo.average_cost() == Money::new(2, 0));
+
+        // all of lot 2 and half of lot 1
+        assert_eq!(lifo.lots()[0].id(), 1);
+        assert!(lifo.average_cost() == Money::new(1, 0));
+
+        assert_eq!(specific.closed_lots()[0].lot_id(), 2);
+        assert_eq!(specific.lots()[0].id(), 1);
+
+        assert!(fifo.realized_profit() == Money::new(500, 0));
+        assert_eq!(fifo.quantity(), 1);
+    }
+
+    #[test]
+    fn test_realized_and_unrealized_profit() {
+        let (mut position, quote) = position_with_two_lots(LotMatching::Fifo);
+
+        let sell = Order::new_sell_close_order(&quote, 1, quote.bid());
+        position.apply_order(fill(&quote, sell, Money::new(1, 50), 4));
+
+        // half of the $1.00 lot was closed for $0.50 a share
+        assert!(position.realized_profit() == Money::new(50, 0));
+        assert!(position.cost_basis() == Money::new(500, 0));
+
+        // 3 contracts left: at the $1.00 bid, the $1.05 mid or the $1.00 last
+        assert!(position.unrealized_profit(&quote, MarkMethod::BidAsk) == Money::new(-200, 0));
+        assert!(position.unrealized_profit(&quote, MarkMethod::Mid) == Money::new(-185, 0));
+        assert!(position.unrealized_profit(&quote, MarkMethod::Last) == Money::new(-200, 0));
+    }
+
+    #[test]
+    fn test_apply_split() {
+        let quote = dummy_quote(Money::new(14, 0), Money::new(14, 20));
+        let order = Order::new_buy_open_order(&quote, 2, quote.ask());
+
+        let mut position = Position::new(&quote);
+        position.apply_order(Rc::new(FilledOrder::new(order, &quote, quote.ask(), Utc::now())));
+
+        let mut whole = position.clone();
+        whole.apply_split(4, 1);
+
+        assert_eq!(whole.quantity(), 8);
+        assert_eq!(whole.multiplier(), 100);
+        assert!(whole.strike_price() == Money::new(25, 0));
+
+        let name = quote::option_name("AAPL", quote.expiration_date(), true, Money::new(25, 0));
+        assert_eq!(&*whole.name(), name.as_str());
+
+        let mut fractional = position.clone();
+        fractional.apply_split(3, 2);
+
+        assert_eq!(fractional.quantity(), 2);
+        assert_eq!(fractional.multiplier(), 150);
+        assert!(fractional.strike_price() == Money::new(66, 67));
     }
 
 }
@@ -0,0 +1,114 @@
+// Black-Scholes pricing for European options.  American options on stocks
+// that don't pay dividends are worth the same as their European counterparts
+// (for calls, and close enough for puts) so this is good enough for stress
+// testing positions.
+
+use std::f64::consts::PI;
+
+pub static DAYS_PER_YEAR: f64 = 365.0;
+
+// Abramowitz & Stegun 26.2.17 (accurate to ~7.5e-8)
+pub fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.2316419 * x.abs());
+    let poly = t
+        * (0.319381530
+            + t * (-0.356563782 + t * (1.781477937 + t * (-1.821255978 + t * 1.330274429))));
+    let cdf = 1.0 - normal_pdf(x) * poly;
+
+    if x >= 0.0 {
+        cdf
+    } else {
+        1.0 - cdf
+    }
+}
+
+pub fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * PI).sqrt()
+}
+
+// price per share.  volatility and rate are annualized (0.25 is 25%).
+pub fn black_scholes(
+    call: bool,
+    spot: f64,
+    strike: f64,
+    years: f64,
+    rate: f64,
+    volatility: f64,
+) -> f64 {
+    // at (or past) expiration, or with nothing to go on, all that's left is
+    // intrinsic value
+    if years <= 0.0 || volatility <= 0.0 || spot <= 0.0 || strike <= 0.0 {
+        let discounted_strike = strike * (-rate * years.max(0.0)).exp();
+
+        return if call {
+            (spot - discounted_strike).max(0.0)
+        } else {
+            (discounted_strike - spot).max(0.0)
+        };
+    }
+
+    let (d1, d2) = d1_d2(spot, strike, years, rate, volatility);
+    let discounted_strike = strike * (-rate * years).exp();
+
+    if call {
+        spot * normal_cdf(d1) - discounted_strike * normal_cdf(d2)
+    } else {
+        discounted_strike * normal_cdf(-d2) - spot * normal_cdf(-d1)
+    }
+}
+
+// change in price per share over a year (not per day), holding everything
+// else constant.  zero at (or past) expiration.
+pub fn theta(call: bool, spot: f64, strike: f64, years: f64, rate: f64, volatility: f64) -> f64 {
+    if years <= 0.0 || volatility <= 0.0 || spot <= 0.0 || strike <= 0.0 {
+        return 0.0;
+    }
+
+    let (d1, d2) = d1_d2(spot, strike, years, rate, volatility);
+    let decay = -spot * normal_pdf(d1) * volatility / (2.0 * years.sqrt());
+    let discounted_strike = strike * (-rate * years).exp();
+
+    if call {
+        decay - rate * discounted_strike * normal_cdf(d2)
+    } else {
+        decay + rate * discounted_strike * normal_cdf(-d2)
+    }
+}
+
+fn d1_d2(spot: f64, strike: f64, years: f64, rate: f64, volatility: f64) -> (f64, f64) {
+    let d1 = ((spot / strike).ln() + (rate + volatility * volatility / 2.0) * years)
+        / (volatility * years.sqrt());
+
+    (d1, d1 - volatility * years.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_black_scholes() {
+        // Hull, Options Futures & Other Derivatives, example 15.6
+        let call = black_scholes(true, 42.0, 40.0, 0.5, 0.1, 0.2);
+        let put = black_scholes(false, 42.0, 40.0, 0.5, 0.1, 0.2);
+
+        assert!((call - 4.76).abs() < 0.01);
+        assert!((put - 0.81).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_theta() {
+        // Hull, Options Futures & Other Derivatives, example 19.2
+        let call = theta(true, 49.0, 50.0, 0.3846, 0.05, 0.2);
+
+        assert!((call - -4.31).abs() < 0.01);
+        assert_eq!(theta(false, 49.0, 50.0, 0.0, 0.05, 0.2), 0.0);
+    }
+
+    #[test]
+    fn test_intrinsic_at_expiration() {
+        assert_eq!(black_scholes(true, 105.0, 100.0, 0.0, 0.05, 0.3), 5.0);
+        assert_eq!(black_scholes(false, 105.0, 100.0, 0.0, 0.05, 0.3), 0.0);
+    }
+}
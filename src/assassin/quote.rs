@@ -6,6 +6,26 @@ use self::chrono::prelude::*;
 extern crate greenback;
 use greenback::Greenback as Money;
 
+// the name a Quote for this series would have
+pub fn option_name(
+    symbol: &str,
+    expiration_date: DateTime<Utc>,
+    call: bool,
+    strike_price: Money,
+) -> String {
+    format!(
+        "{symbol}{year}{month}{day}{t}{price:>0width$}0",
+        symbol = symbol,
+        year = expiration_date.year(),
+        month = expiration_date.month(),
+        day = expiration_date.day(),
+        t = if call { "C" } else { "P" },
+        // this used to be multiplied by 100 but raw_value() is the same thing
+        price = strike_price.raw_value(),
+        width = 7,
+    )
+}
+
 #[derive(Clone)]
 pub struct Quote {
     symbol: Rc<str>,
@@ -45,17 +65,7 @@ impl Quote {
         underlying_price: Money,
         date: DateTime<Utc>,
     ) -> Quote {
-        let name = format!(
-            "{symbol}{year}{month}{day}{t}{price:>0width$}0",
-            symbol = symbol,
-            year = expiration_date.year(),
-            month = expiration_date.month(),
-            day = expiration_date.day(),
-            t = if call { "C" } else { "P" },
-            // this used to be multiplied by 100 but raw_value() is the same thing
-            price = strike_price.raw_value(),
-            width = 7,
-        );
+        let name = option_name(&symbol, expiration_date, call, strike_price);
 
         let symbol_ref: &str = &symbol;
         let name_ref: &str = &name;
@@ -109,6 +119,10 @@ impl Quote {
         self.ask
     }
 
+    pub fn last_price(&self) -> Money {
+        self.last_price
+    }
+
     pub fn expiration_date(&self) -> DateTime<Utc> {
         self.expiration_date
     }
@@ -117,6 +131,25 @@ impl Quote {
         self.expiration_date.num_days_from_ce() - current_date.num_days_from_ce()
     }
 
+    pub fn implied_volatility(&self) -> f32 {
+        self.implied_volatility
+    }
+
+    // per share, as supplied by the feed
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    // change in delta for a $1 move in the underlying
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    // change in price per share for a 1 point move in implied volatility
+    pub fn vega(&self) -> f32 {
+        self.vega
+    }
+
     pub fn underlying_price(&self) -> Money {
         self.underlying_price
     }
@@ -0,0 +1,110 @@
+use assassin::quote::Quote;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Quotes for tests, without spelling out all of Quote::new()'s arguments.
+// Starts out as an AAPL $100 call expiring 2013-06-21, quoted $1.00 / $1.10
+// on 2013-01-02 with the underlying at $100.  The last price is the bid.
+pub struct QuoteBuilder {
+    expiration_date: DateTime<Utc>,
+    ask: Money,
+    bid: Money,
+    call: bool,
+    strike_price: Money,
+    implied_volatility: f32,
+    delta: f32,
+    gamma: f32,
+    vega: f32,
+    underlying_price: Money,
+    date: DateTime<Utc>,
+}
+
+impl QuoteBuilder {
+    pub fn new() -> QuoteBuilder {
+        QuoteBuilder {
+            expiration_date: Utc.ymd(2013, 6, 21).and_hms(0, 0, 0),
+            ask: Money::new(1, 10),
+            bid: Money::new(1, 0),
+            call: true,
+            strike_price: Money::new(100, 0),
+            implied_volatility: 0.0,
+            delta: 0.0,
+            gamma: 0.0,
+            vega: 0.0,
+            underlying_price: Money::new(100, 0),
+            date: Utc.ymd(2013, 1, 2).and_hms(0, 0, 0),
+        }
+    }
+
+    pub fn expiring(mut self, year: i32, month: u32, day: u32) -> QuoteBuilder {
+        self.expiration_date = Utc.ymd(year, month, day).and_hms(0, 0, 0);
+        self
+    }
+
+    pub fn quoted_on(mut self, year: i32, month: u32, day: u32) -> QuoteBuilder {
+        self.date = Utc.ymd(year, month, day).and_hms(0, 0, 0);
+        self
+    }
+
+    pub fn with_call(mut self, call: bool) -> QuoteBuilder {
+        self.call = call;
+        self
+    }
+
+    pub fn with_strike(mut self, strike: i32) -> QuoteBuilder {
+        self.strike_price = Money::new(strike, 0);
+        self
+    }
+
+    pub fn with_bid_ask(mut self, bid: Money, ask: Money) -> QuoteBuilder {
+        self.bid = bid;
+        self.ask = ask;
+        self
+    }
+
+    // no spread
+    pub fn with_price(self, price: Money) -> QuoteBuilder {
+        self.with_bid_ask(price, price)
+    }
+
+    pub fn with_implied_volatility(mut self, implied_volatility: f32) -> QuoteBuilder {
+        self.implied_volatility = implied_volatility;
+        self
+    }
+
+    pub fn with_greeks(mut self, delta: f32, gamma: f32, vega: f32) -> QuoteBuilder {
+        self.delta = delta;
+        self.gamma = gamma;
+        self.vega = vega;
+        self
+    }
+
+    pub fn with_underlying(mut self, underlying_price: Money) -> QuoteBuilder {
+        self.underlying_price = underlying_price;
+        self
+    }
+
+    pub fn build(self) -> Quote {
+        Quote::new(
+            "AAPL".to_string(),
+            self.expiration_date,
+            self.ask,
+            self.bid,
+            self.bid,
+            self.call,
+            self.strike_price,
+            0,
+            self.implied_volatility,
+            self.delta,
+            self.gamma,
+            self.vega,
+            0,
+            self.underlying_price,
+            self.date,
+        )
+    }
+}
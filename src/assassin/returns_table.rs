@@ -0,0 +1,146 @@
+use assassin::equity_point::EquityPoint;
+use assassin::json::Json;
+use assassin::stats;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+pub static MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// Returns broken down by month and year, to see whether a strategy made its
+// money steadily or in a few good stretches.  0.01 is 1%.
+#[derive(Clone)]
+pub struct ReturnsTable {
+    // (year, month, return) in order, January is 1
+    months: Vec<(i32, u32, f64)>,
+    years: Vec<(i32, f64)>,
+}
+
+impl ReturnsTable {
+    pub fn new(opening_balance: Money, equity_curve: &[EquityPoint]) -> ReturnsTable {
+        ReturnsTable {
+            months: stats::monthly_returns(opening_balance, equity_curve),
+            years: stats::yearly_returns(opening_balance, equity_curve),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn months(&self) -> &Vec<(i32, u32, f64)> {
+        &self.months
+    }
+
+    pub fn years(&self) -> &Vec<(i32, f64)> {
+        &self.years
+    }
+
+    pub fn month(&self, year: i32, month: u32) -> Option<f64> {
+        self.months
+            .iter()
+            .find(|&&(y, m, _)| y == year && m == month)
+            .map(|&(_, _, r)| r)
+    }
+
+    pub fn best_month(&self) -> Option<(i32, u32, f64)> {
+        self.months
+            .iter()
+            .cloned()
+            .fold(None, |best, m| match best {
+                Some(b) if b.2 >= m.2 => Some(b),
+                _ => Some(m),
+            })
+    }
+
+    pub fn worst_month(&self) -> Option<(i32, u32, f64)> {
+        self.months
+            .iter()
+            .cloned()
+            .fold(None, |worst, m| match worst {
+                Some(w) if w.2 <= m.2 => Some(w),
+                _ => Some(m),
+            })
+    }
+
+    // 0.6 is 60%
+    pub fn positive_months(&self) -> f64 {
+        let positive = self.months.iter().filter(|&&(_, _, r)| r > 0.0).count();
+
+        stats::ratio(positive as f64, self.months.len() as f64)
+    }
+
+    pub fn to_json(&self) -> Json {
+        let month = |m: (i32, u32, f64)| {
+            Json::Object(vec![
+                ("year", Json::number(m.0)),
+                ("month", Json::number(m.1)),
+                ("return", Json::Number(m.2)),
+            ])
+        };
+
+        Json::Object(vec![
+            ("monthly", Json::Array(self.months.iter().map(|&m| month(m)).collect())),
+            (
+                "yearly",
+                Json::Array(
+                    self.years
+                        .iter()
+                        .map(|&(year, r)| {
+                            Json::Object(vec![
+                                ("year", Json::number(year)),
+                                ("return", Json::Number(r)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            ("best_month", Json::optional(self.best_month(), month)),
+            ("worst_month", Json::optional(self.worst_month(), month)),
+            ("positive_months", Json::Number(self.positive_months())),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    extern crate chrono;
+    use self::chrono::prelude::*;
+
+    fn point(year: i32, month: u32, day: u32, balance: i32) -> EquityPoint {
+        EquityPoint::new(
+            Utc.ymd(year, month, day).and_hms(0, 0, 0),
+            Money::new(balance, 0),
+            Money::zero(),
+            Money::zero(),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_table() {
+        let curve = vec![
+            point(2012, 11, 30, 110),
+            point(2012, 12, 3, 90),
+            point(2012, 12, 31, 99),
+            point(2013, 1, 2, 99),
+            point(2013, 1, 31, 118),
+        ];
+
+        let table = ReturnsTable::new(Money::new(100, 0), &curve);
+
+        assert_eq!(table.months().len(), 3);
+        assert!((table.month(2012, 12).unwrap() + 0.1).abs() < 1e-9);
+        assert_eq!(table.month(2013, 2), None);
+
+        // 100 -> 99 in 2012 and 99 -> 118 in 2013
+        assert!((table.years()[0].1 + 0.01).abs() < 1e-9);
+        assert_eq!(table.years()[1].0, 2013);
+
+        assert_eq!(table.best_month().map(|m| (m.0, m.1)), Some((2013, 1)));
+        assert_eq!(table.worst_month().map(|m| (m.0, m.1)), Some((2012, 12)));
+        assert!((table.positive_months() - 2.0 / 3.0).abs() < 1e-9);
+    }
+}
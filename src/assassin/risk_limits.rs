@@ -0,0 +1,272 @@
+use std::fmt::Display;
+
+use assassin::filled_order::FilledOrder;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Limits the broker checks before filling an order that opens (or adds to)
+// positions, to keep a strategy with a bug from blowing up the account.
+// Orders that would breach one are rejected with the reason, the same way
+// orders without enough buying power are.  Orders that only close positions
+// are always allowed.  Every limit is off until it's set.
+#[derive(Clone)]
+pub struct RiskLimits {
+    // a combo counts its largest leg
+    max_contracts_per_order: Option<i32>,
+    max_contracts_per_position: Option<i32>,
+    // contracts * shares per contract * underlying price, plus any shares
+    max_notional_per_underlying: Option<Money>,
+    // see Greeks::dollar_delta()
+    max_portfolio_delta: Option<f64>,
+    max_portfolio_vega: Option<f64>,
+    // see max_loss().  orders that can lose an unlimited amount are rejected
+    // when this is set.
+    max_loss_per_trade: Option<Money>,
+    // the gross market value of options expiring on a single date, as a
+    // percent of the unrealized account balance
+    max_expiration_percent: Option<f64>,
+}
+
+impl RiskLimits {
+    pub fn new() -> RiskLimits {
+        RiskLimits {
+            max_contracts_per_order: None,
+            max_contracts_per_position: None,
+            max_notional_per_underlying: None,
+            max_portfolio_delta: None,
+            max_portfolio_vega: None,
+            max_loss_per_trade: None,
+            max_expiration_percent: None,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_max_contracts_per_order(mut self, contracts: i32) -> RiskLimits {
+        if contracts < 1 {
+            panic!("contracts must be >= 1 (got {})", contracts);
+        }
+
+        self.max_contracts_per_order = Some(contracts);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_max_contracts_per_position(mut self, contracts: i32) -> RiskLimits {
+        if contracts < 1 {
+            panic!("contracts must be >= 1 (got {})", contracts);
+        }
+
+        self.max_contracts_per_position = Some(contracts);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_max_notional_per_underlying(mut self, notional: Money) -> RiskLimits {
+        if notional <= Money::zero() {
+            panic!("notional must be > 0.0 (got {})", notional);
+        }
+
+        self.max_notional_per_underlying = Some(notional);
+        self
+    }
+
+    // in either direction
+    #[allow(dead_code)]
+    pub fn with_max_portfolio_delta(mut self, dollar_delta: f64) -> RiskLimits {
+        if dollar_delta <= 0.0 {
+            panic!("dollar delta must be > 0.0 (got {})", dollar_delta);
+        }
+
+        self.max_portfolio_delta = Some(dollar_delta);
+        self
+    }
+
+    // in either direction
+    #[allow(dead_code)]
+    pub fn with_max_portfolio_vega(mut self, vega: f64) -> RiskLimits {
+        if vega <= 0.0 {
+            panic!("vega must be > 0.0 (got {})", vega);
+        }
+
+        self.max_portfolio_vega = Some(vega);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_max_loss_per_trade(mut self, loss: Money) -> RiskLimits {
+        if loss <= Money::zero() {
+            panic!("loss must be > 0.0 (got {})", loss);
+        }
+
+        self.max_loss_per_trade = Some(loss);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_max_expiration_percent(mut self, percent: f64) -> RiskLimits {
+        if percent <= 0.0 {
+            panic!("percent must be > 0.0 (got {})", percent);
+        }
+
+        self.max_expiration_percent = Some(percent);
+        self
+    }
+
+    pub fn max_contracts_per_order(&self) -> Option<i32> {
+        self.max_contracts_per_order
+    }
+
+    pub fn max_contracts_per_position(&self) -> Option<i32> {
+        self.max_contracts_per_position
+    }
+
+    pub fn max_notional_per_underlying(&self) -> Option<Money> {
+        self.max_notional_per_underlying
+    }
+
+    pub fn max_portfolio_delta(&self) -> Option<f64> {
+        self.max_portfolio_delta
+    }
+
+    pub fn max_portfolio_vega(&self) -> Option<f64> {
+        self.max_portfolio_vega
+    }
+
+    pub fn max_loss_per_trade(&self) -> Option<Money> {
+        self.max_loss_per_trade
+    }
+
+    pub fn max_expiration_percent(&self) -> Option<f64> {
+        self.max_expiration_percent
+    }
+}
+
+// the reason an order gets rejected if `value` is over `limit`
+pub fn check<T>(limit: Option<T>, value: T, what: &str) -> Result<(), String>
+where
+    T: PartialOrd + Display,
+{
+    match limit {
+        Some(ref limit) if value > *limit => Err(format!(
+            "risk limit: {} of {} is over the limit of {}",
+            what,
+            value,
+            limit,
+        )),
+        _ => Ok(()),
+    }
+}
+
+// The most an order's legs can lose together by expiration, counting what was
+// paid or received for them (commissions included), or None when there's no
+// limit to it (short calls that aren't covered by long ones).  Each leg comes
+// with the shares its contracts deliver.  Legs are only worth their intrinsic
+// value at expiration, so calendars and diagonals come out a little worse
+// than they are.
+pub fn max_loss(legs: &[(&FilledOrder, i32)]) -> Option<Money> {
+    // shares that gain as the underlying goes up without a bound
+    let calls: i32 = legs
+        .iter()
+        .filter(|&&(o, _)| o.quote().is_call())
+        .map(|&(o, multiplier)| o.canonical_quantity() * multiplier)
+        .sum();
+
+    if calls < 0 {
+        return None;
+    }
+
+    let cash: Money = legs
+        .iter()
+        .map(|&(o, _)| o.canonical_cost_basis() - o.commission())
+        .sum();
+
+    // the payoff only bends at the strikes, so the worst of it is at one of
+    // them or at zero
+    let mut prices: Vec<Money> = legs.iter().map(|&(o, _)| o.quote().strike_price()).collect();
+    prices.push(Money::zero());
+
+    let worst = prices
+        .iter()
+        .map(|&price| {
+            legs.iter()
+                .map(|&(o, multiplier)| {
+                    let quote = o.quote();
+                    let intrinsic = if quote.is_call() {
+                        price - quote.strike_price()
+                    } else {
+                        quote.strike_price() - price
+                    };
+
+                    if intrinsic > Money::zero() {
+                        intrinsic * multiplier * o.canonical_quantity()
+                    } else {
+                        Money::zero()
+                    }
+                })
+                .sum::<Money>()
+        })
+        .min()
+        .unwrap()
+        + cash;
+
+    if worst < Money::zero() {
+        Some(Money::zero() - worst)
+    } else {
+        Some(Money::zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::order::Order;
+    use assassin::quote::Quote;
+    use assassin::quote_builder::QuoteBuilder;
+
+    extern crate chrono;
+    use self::chrono::prelude::*;
+
+    // buys when quantity > 0, sells when it's < 0
+    fn fill(quote: &Quote, quantity: i32, price: Money) -> FilledOrder {
+        let order = if quantity > 0 {
+            Order::new_buy_open_order(quote, quantity, price)
+        } else {
+            Order::new_sell_open_order(quote, -quantity, price)
+        };
+
+        FilledOrder::new(order, quote, price, Utc::now())
+    }
+
+    #[test]
+    fn test_check() {
+        let limits = RiskLimits::new().with_max_contracts_per_order(10);
+
+        assert!(check(limits.max_contracts_per_order(), 10, "contracts per order").is_ok());
+        assert_eq!(
+            check(limits.max_contracts_per_order(), 11, "contracts per order"),
+            Err("risk limit: contracts per order of 11 is over the limit of 10".to_string())
+        );
+        assert!(check(limits.max_contracts_per_position(), 1_000, "contracts").is_ok());
+    }
+
+    #[test]
+    fn test_max_loss() {
+        let call = |strike| QuoteBuilder::new().with_strike(strike).build();
+        let (short, long) = (call(100), call(105));
+
+        // a long option can lose what was paid for it
+        let bought = fill(&long, 2, Money::new(1, 0));
+        assert!(max_loss(&[(&bought, 100)]) == Some(Money::new(200, 0)));
+
+        // a $5 wide credit spread for $2 can lose $3
+        let sold = fill(&short, -1, Money::new(3, 0));
+        let bought = fill(&long, 1, Money::new(1, 0));
+        assert!(max_loss(&[(&sold, 100), (&bought, 100)]) == Some(Money::new(300, 0)));
+
+        // and a naked one has no limit
+        assert!(max_loss(&[(&sold, 100)]).is_none());
+    }
+}
@@ -1,6 +1,18 @@
+use std::cmp::Ordering;
+use std::io;
 use std::time::Instant;
 
+use assassin::backtest_result::BacktestResult;
+use assassin::benchmark::{Benchmark, BenchmarkStats};
 use assassin::broker::Broker;
+use assassin::greeks::Greeks;
+use assassin::lot::HoldingPeriod;
+use assassin::returns_table::{ReturnsTable, MONTHS};
+use assassin::stats::PerformanceStats;
+use assassin::submitted_order::OrderStatus;
+use assassin::targets::{Targets, Verdict};
+use assassin::tear_sheet;
+use assassin::trade_stats::TradeStats;
 use assassin::traits::*;
 
 extern crate greenback;
@@ -8,13 +20,20 @@ use greenback::Greenback as Money;
 use greenback::util::add_commas;
 
 pub struct Simulation {
-    model: Box<Model>,
+    // run in the order they were added, each seeing the orders of the ones
+    // before it
+    models: Vec<Box<Model>>,
     broker: Box<Broker>,
 
     // TODO: add settings variables (slippage, spread multipliers, etc.)
-    // TODO: add target stats that the model must hit (sharpe, DD, etc.)
+    targets: Targets,
     start_time: Instant,
     starting_balance: Money,
+    exercise_events_delivered: usize,
+    // annualized, for the Sharpe and Sortino ratios
+    risk_free_rate: f64,
+    // buy-and-hold of the underlying when there isn't one
+    benchmark: Option<Benchmark>,
 }
 
 impl Simulation {
@@ -22,25 +41,139 @@ impl Simulation {
         let starting_balance = broker.account_balance();
 
         Simulation {
-            model: model,
+            models: vec![model],
             broker: broker,
+            targets: Targets::new(),
             start_time: Instant::now(),
             starting_balance: starting_balance,
+            exercise_events_delivered: 0,
+            risk_free_rate: 0.0,
+            benchmark: None,
         }
     }
 
+    #[allow(dead_code)]
+    pub fn set_risk_free_rate(&mut self, rate: f64) {
+        self.risk_free_rate = rate;
+    }
+
+    pub fn performance_stats(&self) -> PerformanceStats {
+        PerformanceStats::new(
+            self.starting_balance,
+            self.broker.equity_curve(),
+            self.risk_free_rate,
+        )
+    }
+
+    pub fn set_benchmark(&mut self, benchmark: Benchmark) {
+        self.benchmark = Some(benchmark);
+    }
+
+    // against the benchmark that was set, or buying and holding the first
+    // underlying (alphabetically) that was quoted
+    pub fn benchmark_stats(&self) -> Option<BenchmarkStats> {
+        let benchmark = match self.benchmark {
+            Some(ref b) => b.clone(),
+            None => {
+                let symbol = self.broker.underlying_symbols().into_iter().next()?;
+                Benchmark::buy_and_hold(&self.broker, &symbol)?
+            }
+        };
+
+        Some(BenchmarkStats::new(
+            &benchmark,
+            self.starting_balance,
+            self.broker.equity_curve(),
+            self.risk_free_rate,
+        ))
+    }
+
+    pub fn trade_stats(&self) -> TradeStats {
+        TradeStats::new(&self.broker.trades())
+    }
+
+    #[allow(dead_code)]
+    pub fn set_targets(&mut self, targets: Targets) {
+        self.targets = targets;
+    }
+
+    // everything about the run, for exporting.  only meaningful once it's over.
+    pub fn result(&self) -> BacktestResult {
+        BacktestResult::new(
+            &self.broker,
+            self.models.iter().map(|m| m.name()).collect(),
+            self.risk_free_rate,
+            self.performance_stats(),
+            self.benchmark_stats(),
+            self.verdict(),
+        )
+    }
+
+    pub fn write_tear_sheet(&self, filename: &str) -> io::Result<()> {
+        tear_sheet::write(&self.result(), filename)
+    }
+
+    pub fn write_csv(&self, directory: &str) -> io::Result<()> {
+        self.broker.write_csv(directory)
+    }
+
+    pub fn has_targets(&self) -> bool {
+        !self.targets.is_empty()
+    }
+
+    // how the run did against its targets.  only meaningful once it's over.
+    pub fn verdict(&self) -> Verdict {
+        self.targets.evaluate(
+            &self.performance_stats(),
+            &self.trade_stats(),
+            self.broker.commission_paid(),
+            self.broker.account_balance() - self.starting_balance,
+        )
+    }
+
+    // runs another model against the same broker.  models that share a
+    // broker should tag their orders with a TradeGroup so they can tell their
+    // positions apart.
+    #[allow(dead_code)]
+    pub fn add_model(&mut self, model: Box<Model>) {
+        self.models.push(model);
+    }
+
     pub fn run(&mut self) {
-        self.model.before_simulation(&mut *self.broker);
+        for model in self.models.iter_mut() {
+            model.before_simulation(&mut *self.broker);
+        }
 
         while self.broker.process_simulation_data() {
-            let orders = self.model.run_logic(&self.broker);
+            self.deliver_exercise_events();
+
+            for model in self.models.iter_mut() {
+                let requests = model.run_logic(&self.broker);
+
+                for r in requests {
+                    self.broker.process_order_request(r);
+                }
+            }
+        }
+
+        self.deliver_exercise_events();
+
+        for model in self.models.iter_mut() {
+            model.after_simulation(&mut *self.broker);
+        }
+    }
 
-            for o in orders {
-                self.broker.process_order(o);
+    // hands the models anything that was exercised or assigned since last time
+    fn deliver_exercise_events(&mut self) {
+        let events = &self.broker.exercise_events()[self.exercise_events_delivered..];
+
+        for e in events {
+            for model in self.models.iter_mut() {
+                model.handle_exercise_event(&self.broker, e);
             }
         }
 
-        self.model.after_simulation(&mut *self.broker);
+        self.exercise_events_delivered += events.len();
     }
 
     pub fn print_stats(&self) {
@@ -74,12 +207,177 @@ impl Simulation {
             }
             info!("");
 
+            for l in pos.closed_lots() {
+                info!(
+                    "  lot #{}: {} contracts {} -> {} held {} days ({:?}): {}",
+                    l.lot_id(),
+                    l.quantity(),
+                    l.open_price(),
+                    l.close_price(),
+                    l.days_held(),
+                    l.holding_period(),
+                    l.realized_profit(),
+                );
+            }
+
             info!("Commission paid: {}", pos.commission_paid());
             info!("Position value: {}", pos.realized_profit());
             info!("Running total: {}", running_total);
             info!("");
         }
 
+        let exercise_events = self.broker.exercise_events();
+
+        if !exercise_events.is_empty() {
+            info!("===== EXPIRATIONS =====");
+            info!("");
+
+            for e in exercise_events {
+                info!("{} {}", e.date().format("%Y-%m-%d"), e.summary());
+
+                if e.shares() != 0 {
+                    info!("  {} shares of {} for {}", e.shares(), e.symbol(), e.cash());
+                } else if e.cash() != Money::zero() {
+                    info!("  settled for {}", e.cash());
+                }
+            }
+            info!("");
+        }
+
+        let stock_positions = self.broker.stock_positions();
+
+        if !stock_positions.is_empty() {
+            info!("===== STOCK =====");
+            info!("");
+
+            for s in &stock_positions {
+                info!(
+                    "{}: {} shares held ({} cost basis) - Realized profit: {}",
+                    s.symbol(),
+                    s.quantity(),
+                    s.cost_basis(),
+                    s.realized_profit(),
+                );
+            }
+            info!("");
+        }
+
+        let corporate_actions = self.broker.applied_corporate_actions();
+
+        if !corporate_actions.is_empty() {
+            info!("===== CORPORATE ACTIONS =====");
+            info!("");
+
+            for a in corporate_actions {
+                info!("{}", a.summary());
+            }
+            info!("");
+        }
+
+        let breaker_events = self.broker.circuit_breaker_events();
+
+        if !breaker_events.is_empty() {
+            info!("===== CIRCUIT BREAKERS =====");
+            info!("");
+
+            for e in breaker_events {
+                info!(
+                    "{} {}: {}",
+                    e.date().format("%Y-%m-%d"),
+                    e.action().name(),
+                    e.reason(),
+                );
+            }
+            info!("");
+        }
+
+        let combo_ids = self.broker.combo_ids();
+
+        if !combo_ids.is_empty() {
+            info!("===== COMBOS =====");
+            info!("");
+
+            for id in combo_ids {
+                info!("----- combo #{} -----", id);
+
+                let mut net = Money::zero();
+
+                for pos in self.broker.positions_for_combo(id) {
+                    for o in pos.orders().iter().filter(|o| o.combo_id() == Some(id)) {
+                        net = net + o.canonical_cost_basis() - o.commission();
+
+                        info!(
+                            "  {} {} {} contracts @ {}",
+                            o.buy_or_sell_string(),
+                            o.quantity(),
+                            o.option_name(),
+                            o.fill_price(),
+                        );
+                    }
+                }
+
+                info!("Net premium (after commission): {}", net);
+                info!("");
+            }
+        }
+
+        let greeks_history = self.broker.greeks_history();
+
+        if !greeks_history.is_empty() {
+            info!("===== GREEKS =====");
+            info!("");
+
+            let largest = |value: &Fn(&Greeks) -> f64| {
+                greeks_history
+                    .iter()
+                    .max_by(|a, b| {
+                        let (a, b) = (value(&a.1).abs(), value(&b.1).abs());
+                        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+                    })
+                    .map(|&(date, ref g)| (date.format("%Y-%m-%d"), value(g)))
+                    .unwrap()
+            };
+
+            let (date, dollar_delta) = largest(&|g| g.dollar_delta());
+            info!("Largest dollar delta: ${:.0} on {}", dollar_delta, date);
+
+            let (date, weighted) = largest(&|g| g.beta_weighted_delta());
+            info!("Largest beta weighted delta: ${:.0} on {}", weighted, date);
+
+            let (date, vega) = largest(&|g| g.vega());
+            info!("Largest vega: ${:.2} on {}", vega, date);
+
+            let theta: f64 = greeks_history.iter().map(|(_, g)| g.theta()).sum();
+            info!("Theta (sum of daily): ${:.2}", theta);
+
+            let &(date, ref last) = greeks_history.last().unwrap();
+            info!("Last ({}): {}", date.format("%Y-%m-%d"), last.summary());
+            info!("");
+        }
+
+        let trade_groups = self.broker.all_trade_groups();
+
+        if !trade_groups.is_empty() {
+            info!("===== TRADE GROUPS =====");
+            info!("");
+
+            for group in trade_groups {
+                let legs: Vec<String> = self.broker
+                    .positions_in_group(&group)
+                    .iter()
+                    .map(|p| p.name().to_string())
+                    .collect();
+
+                info!(
+                    "{}: {} ({})",
+                    group,
+                    self.broker.trade_group_pnl(&group),
+                    legs.join(", "),
+                );
+            }
+            info!("");
+        }
+
         let balance_change = balance - self.starting_balance;
 
         info!("===== RESULTS =====");
@@ -131,6 +429,236 @@ impl Simulation {
             commish_percent_of_profit,
         );
         info!("Average commission per order: {}", average_commission);
+        info!(
+            "Orders submitted: {} ({} filled, {} rejected, {} cancelled, {} working)",
+            self.broker.submitted_orders().len(),
+            self.broker.submitted_orders_with_status(OrderStatus::Filled).len(),
+            self.broker.submitted_orders_with_status(OrderStatus::Rejected).len(),
+            self.broker.submitted_orders_with_status(OrderStatus::Cancelled).len(),
+            self.broker.submitted_orders_with_status(OrderStatus::Working).len(),
+        );
+
+        for o in self.broker.rejected_orders() {
+            info!(
+                "  #{} {} rejected: {} - {}",
+                o.id(),
+                o.status_date().format("%Y-%m-%d"),
+                o.summary(),
+                o.reason().unwrap_or(""),
+            );
+        }
+
+        info!("Interest earned: {}", self.broker.interest_earned());
+        info!("Interest paid: {}", self.broker.interest_paid());
+        info!("Dividends received: {}", self.broker.dividends_received());
+        info!(
+            "Realized profit on closed lots: {} short term, {} long term",
+            self.broker.realized_lot_profit(HoldingPeriod::ShortTerm),
+            self.broker.realized_lot_profit(HoldingPeriod::LongTerm),
+        );
+
+        match self.broker.reconcile_journal() {
+            Ok(()) => info!("Journal entries: {} (reconciled)", self.broker.journal().len()),
+            Err(reason) => warn!("Journal does not reconcile: {}", reason),
+        }
+
+        for usage in self.broker.margin_usage() {
+            info!(
+                "Margin ({}{}): highest requirement {} - lowest buying power {} - \
+                 {:.2}% return on the highest requirement",
+                usage.calculator(),
+                if usage.is_enforced() { "" } else { ", not enforced" },
+                usage.highest_requirement(),
+                usage.lowest_buying_power(),
+                usage.return_on_requirement(balance_change) * 100.0,
+            );
+        }
+
+        let daily_pnl = self.broker.daily_account_pnl();
+
+        if let Some(&(date, pnl)) = daily_pnl.iter().max_by_key(|&&(_, pnl)| pnl) {
+            info!("Best day: {} ({})", pnl, date.format("%Y-%m-%d"));
+        }
+
+        if let Some(&(date, pnl)) = daily_pnl.iter().min_by_key(|&&(_, pnl)| pnl) {
+            info!("Worst day: {} ({})", pnl, date.format("%Y-%m-%d"));
+        }
+
+        let equity_curve = self.broker.equity_curve();
+
+        info!("Trading days: {}", equity_curve.len());
+
+        if let Some(p) = equity_curve.iter().max_by_key(|p| p.margin_used()) {
+            info!("Most margin used: {} ({})", p.margin_used(), p.date().format("%Y-%m-%d"));
+        }
+
+        if let Some(p) = equity_curve.iter().max_by_key(|p| p.market_value()) {
+            info!(
+                "Largest market value held: {} ({})",
+                p.market_value(),
+                p.date().format("%Y-%m-%d"),
+            );
+        }
+
+        if let Some(p) = equity_curve.iter().min_by_key(|p| p.cash()) {
+            info!("Lowest cash balance: {} ({})", p.cash(), p.date().format("%Y-%m-%d"));
+        }
+
+        if let Some(p) = equity_curve.iter().max_by_key(|p| p.open_positions()) {
+            info!("Most positions open: {} ({})", p.open_positions(), p.date().format("%Y-%m-%d"));
+        }
+
+        let stats = self.performance_stats();
+
+        info!("");
+        info!("===== PERFORMANCE =====");
+        info!("");
+        info!("Total return: {:.2}%", stats.total_return() * 100.0);
+        info!("Annualized return: {:.2}%", stats.annualized_return() * 100.0);
+        info!("Annualized volatility: {:.2}%", stats.annualized_volatility() * 100.0);
+        info!(
+            "Sharpe ratio: {:.2} - Sortino ratio: {:.2} ({:.2}% risk free)",
+            stats.sharpe_ratio(),
+            stats.sortino_ratio(),
+            self.risk_free_rate * 100.0,
+        );
+        info!(
+            "Max drawdown: {:.2}% ({} days under water) - Calmar ratio: {:.2}",
+            stats.max_drawdown() * 100.0,
+            stats.max_drawdown_days(),
+            stats.calmar_ratio(),
+        );
+        info!(
+            "Daily returns skew: {:.2} - kurtosis: {:.2}",
+            stats.skew(),
+            stats.kurtosis(),
+        );
+        info!("Exposure: {:.2}% of trading days", stats.exposure() * 100.0);
+        info!("");
+
+        if let Some(benchmark) = self.benchmark_stats() {
+            info!("===== BENCHMARK ({}) =====", benchmark.name());
+            info!("");
+            info!(
+                "Total return: {:.2}% - annualized: {:.2}% - max drawdown: {:.2}%",
+                benchmark.performance().total_return() * 100.0,
+                benchmark.performance().annualized_return() * 100.0,
+                benchmark.performance().max_drawdown() * 100.0,
+            );
+            info!(
+                "Alpha: {:.2}% - beta: {:.2} - correlation: {:.2}",
+                benchmark.alpha() * 100.0,
+                benchmark.beta(),
+                benchmark.correlation(),
+            );
+            info!(
+                "Tracking error: {:.2}% - information ratio: {:.2}",
+                benchmark.tracking_error() * 100.0,
+                benchmark.information_ratio(),
+            );
+            info!(
+                "Up capture: {:.2}% - down capture: {:.2}%",
+                benchmark.up_capture() * 100.0,
+                benchmark.down_capture() * 100.0,
+            );
+            info!("");
+        }
+
+        let returns = ReturnsTable::new(self.starting_balance, self.broker.equity_curve());
+
+        info!("===== RETURNS =====");
+        info!("");
+
+        let mut header = "Year  ".to_string();
+
+        for m in MONTHS.iter() {
+            header.push_str(&format!("{:>8}", m));
+        }
+
+        info!("{}{:>9}", header, "Year");
+
+        for &(year, yearly) in returns.years() {
+            let mut row = format!("{}  ", year);
+
+            for month in 1..13 {
+                match returns.month(year, month) {
+                    Some(r) => row.push_str(&format!("{:>7.2}%", r * 100.0)),
+                    None => row.push_str(&format!("{:>8}", "")),
+                }
+            }
+
+            info!("{}{:>8.2}%", row, yearly * 100.0);
+        }
+
+        info!("");
+
+        if let (Some(best), Some(worst)) = (returns.best_month(), returns.worst_month()) {
+            info!(
+                "Best month: {} {} ({:.2}%) - worst month: {} {} ({:.2}%)",
+                MONTHS[best.1 as usize - 1],
+                best.0,
+                best.2 * 100.0,
+                MONTHS[worst.1 as usize - 1],
+                worst.0,
+                worst.2 * 100.0,
+            );
+        }
+
+        info!("Positive months: {:.2}%", returns.positive_months() * 100.0);
+        info!("");
+
+        let trades = self.trade_stats();
+
+        info!("===== TRADES =====");
+        info!("");
+        info!(
+            "Trades: {} - {} winners ({:.2}%) - {} losers ({:.2}%)",
+            trades.count(),
+            trades.winners(),
+            trades.win_rate() * 100.0,
+            trades.losers(),
+            trades.loss_rate() * 100.0,
+        );
+        info!(
+            "Average winner: {} - average loser: {}",
+            trades.average_winner(),
+            trades.average_loser(),
+        );
+        info!(
+            "Largest winner: {} - largest loser: {}",
+            trades.largest_winner(),
+            trades.largest_loser(),
+        );
+        info!(
+            "Profit factor: {:.2} - expectancy: {} per trade",
+            trades.profit_factor(),
+            trades.expectancy(),
+        );
+        info!("Average days held: {:.1}", trades.average_days_held());
+        info!(
+            "Average MAE: {} (worst {}) - average MFE: {}",
+            trades.average_adverse_excursion(),
+            trades.worst_adverse_excursion(),
+            trades.average_favorable_excursion(),
+        );
+        info!(
+            "Closed by the model: {} - closed by the broker: {}",
+            trades.closed_by_model(),
+            trades.closed_by_broker(),
+        );
+        info!("");
+
+        info!("Margin calls: {}", self.broker.margin_calls().len());
+
+        for call in self.broker.margin_calls() {
+            info!(
+                "  {} - equity {} vs requirement {}",
+                call.date().format("%Y-%m-%d"),
+                call.equity(),
+                call.requirement(),
+            );
+        }
+
         info!(
             "Highest realized account balance: {}",
             self.broker.highest_realized_account_balance()
@@ -149,6 +677,19 @@ impl Simulation {
         );
         info!("");
 
+        let verdict = self.verdict();
+
+        info!("===== TARGETS =====");
+        info!("");
+
+        for r in verdict.results() {
+            info!("  {} - {}", if r.passed() { "PASS" } else { "FAIL" }, r.description());
+        }
+
+        info!("");
+        info!("Verdict: {}", if verdict.passed() { "PASS" } else { "FAIL" });
+        info!("");
+
         let quotes_per_sec = self.broker.quotes_processed() as f32 / self.total_run_time();
 
         info!(
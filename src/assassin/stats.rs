@@ -0,0 +1,395 @@
+// Performance statistics worked out from the broker's equity curve.
+//
+// Returns are daily, from the close of one trading day to the next (the first
+// from the opening balance).  Anything that would divide by zero (e.g., the
+// Sharpe ratio of a run that never traded) is 0.0.
+
+use assassin::equity_point::EquityPoint;
+use assassin::json::Json;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+pub static TRADING_DAYS_PER_YEAR: f64 = 252.0;
+static CALENDAR_DAYS_PER_YEAR: f64 = 365.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PerformanceStats {
+    // 0.10 is 10%
+    total_return: f64,
+    annualized_return: f64,
+    annualized_volatility: f64,
+    sharpe_ratio: f64,
+    sortino_ratio: f64,
+    // from the highest equity to the lowest after it.  0.25 is 25%.
+    max_drawdown: f64,
+    // calendar days from the start of the longest drawdown until it
+    // recovered (or the end of the run)
+    max_drawdown_days: i32,
+    calmar_ratio: f64,
+    skew: f64,
+    // excess kurtosis (a normal distribution is 0.0)
+    kurtosis: f64,
+    // fraction of trading days that ended with something held
+    exposure: f64,
+}
+
+impl PerformanceStats {
+    // risk_free_rate is annualized (0.02 is 2%)
+    pub fn new(
+        opening_balance: Money,
+        equity_curve: &[EquityPoint],
+        risk_free_rate: f64,
+    ) -> PerformanceStats {
+        let returns = daily_returns(opening_balance, equity_curve);
+
+        let (total_return, annualized_return) = match equity_curve.last() {
+            Some(last) => {
+                let growth = dollars(last.equity()) / dollars(opening_balance);
+                let first = equity_curve[0].date().num_days_from_ce();
+                let days = (last.date().num_days_from_ce() - first + 1) as f64;
+
+                (
+                    growth - 1.0,
+                    growth.max(0.0).powf(CALENDAR_DAYS_PER_YEAR / days) - 1.0,
+                )
+            }
+            None => (0.0, 0.0),
+        };
+
+        let daily_risk_free = risk_free_rate / TRADING_DAYS_PER_YEAR;
+        let excess: Vec<f64> = returns.iter().map(|r| r - daily_risk_free).collect();
+
+        let volatility = standard_deviation(&returns);
+        let downside = downside_deviation(&excess);
+
+        let (max_drawdown, max_drawdown_days) = max_drawdown(opening_balance, equity_curve);
+
+        let exposure = if equity_curve.is_empty() {
+            0.0
+        } else {
+            equity_curve.iter().filter(|p| p.open_positions() > 0).count() as f64
+                / equity_curve.len() as f64
+        };
+
+        PerformanceStats {
+            total_return: total_return,
+            annualized_return: annualized_return,
+            annualized_volatility: volatility * TRADING_DAYS_PER_YEAR.sqrt(),
+            sharpe_ratio: ratio(mean(&excess), volatility) * TRADING_DAYS_PER_YEAR.sqrt(),
+            sortino_ratio: ratio(mean(&excess), downside) * TRADING_DAYS_PER_YEAR.sqrt(),
+            max_drawdown: max_drawdown,
+            max_drawdown_days: max_drawdown_days,
+            calmar_ratio: ratio(annualized_return, max_drawdown),
+            skew: skew(&returns),
+            kurtosis: kurtosis(&returns),
+            exposure: exposure,
+        }
+    }
+
+    pub fn total_return(&self) -> f64 {
+        self.total_return
+    }
+
+    pub fn annualized_return(&self) -> f64 {
+        self.annualized_return
+    }
+
+    pub fn annualized_volatility(&self) -> f64 {
+        self.annualized_volatility
+    }
+
+    pub fn sharpe_ratio(&self) -> f64 {
+        self.sharpe_ratio
+    }
+
+    pub fn sortino_ratio(&self) -> f64 {
+        self.sortino_ratio
+    }
+
+    pub fn max_drawdown(&self) -> f64 {
+        self.max_drawdown
+    }
+
+    pub fn max_drawdown_days(&self) -> i32 {
+        self.max_drawdown_days
+    }
+
+    pub fn calmar_ratio(&self) -> f64 {
+        self.calmar_ratio
+    }
+
+    pub fn skew(&self) -> f64 {
+        self.skew
+    }
+
+    pub fn kurtosis(&self) -> f64 {
+        self.kurtosis
+    }
+
+    pub fn exposure(&self) -> f64 {
+        self.exposure
+    }
+
+    pub fn to_json(self) -> Json {
+        Json::Object(vec![
+            ("total_return", Json::Number(self.total_return)),
+            ("annualized_return", Json::Number(self.annualized_return)),
+            ("annualized_volatility", Json::Number(self.annualized_volatility)),
+            ("sharpe_ratio", Json::Number(self.sharpe_ratio)),
+            ("sortino_ratio", Json::Number(self.sortino_ratio)),
+            ("max_drawdown", Json::Number(self.max_drawdown)),
+            ("max_drawdown_days", Json::number(self.max_drawdown_days)),
+            ("calmar_ratio", Json::Number(self.calmar_ratio)),
+            ("skew", Json::Number(self.skew)),
+            ("kurtosis", Json::Number(self.kurtosis)),
+            ("exposure", Json::Number(self.exposure)),
+        ])
+    }
+}
+
+// 0.01 is up 1% on the day
+pub fn daily_returns(opening_balance: Money, equity_curve: &[EquityPoint]) -> Vec<f64> {
+    let mut previous = dollars(opening_balance);
+
+    equity_curve
+        .iter()
+        .map(|p| {
+            let equity = dollars(p.equity());
+            let r = ratio(equity - previous, previous);
+            previous = equity;
+            r
+        })
+        .collect()
+}
+
+// how far below its high (so far) the equity was at the close of each day.
+// -0.05 is 5% below.
+pub fn drawdowns(opening_balance: Money, equity_curve: &[EquityPoint]) -> Vec<f64> {
+    let mut peak = dollars(opening_balance);
+
+    equity_curve
+        .iter()
+        .map(|p| {
+            let equity = dollars(p.equity());
+            peak = peak.max(equity);
+            ratio(equity - peak, peak)
+        })
+        .collect()
+}
+
+// (year, month, return) from the close of one month to the close of the next
+// (the first from the opening balance), for every month in the curve
+pub fn monthly_returns(
+    opening_balance: Money,
+    equity_curve: &[EquityPoint],
+) -> Vec<(i32, u32, f64)> {
+    period_returns(opening_balance, equity_curve, |d| (d.year(), d.month()))
+        .into_iter()
+        .map(|((year, month), r)| (year, month, r))
+        .collect()
+}
+
+// (year, return) like monthly_returns()
+pub fn yearly_returns(opening_balance: Money, equity_curve: &[EquityPoint]) -> Vec<(i32, f64)> {
+    period_returns(opening_balance, equity_curve, |d| d.year())
+}
+
+// the return over each run of days that `period` gives the same key
+fn period_returns<K, F>(
+    opening_balance: Money,
+    equity_curve: &[EquityPoint],
+    period: F,
+) -> Vec<(K, f64)>
+where
+    K: PartialEq,
+    F: Fn(DateTime<Utc>) -> K,
+{
+    let mut returns = vec![];
+    let mut start = dollars(opening_balance);
+
+    for (i, p) in equity_curve.iter().enumerate() {
+        let key = period(p.date());
+
+        let period_ends = match equity_curve.get(i + 1) {
+            Some(next) => period(next.date()) != key,
+            None => true,
+        };
+
+        if period_ends {
+            let end = dollars(p.equity());
+            returns.push((key, ratio(end - start, start)));
+            start = end;
+        }
+    }
+
+    returns
+}
+
+// the deepest fall from a high (as a fraction of the high) and the longest
+// it took to make a high back, in calendar days
+fn max_drawdown(opening_balance: Money, equity_curve: &[EquityPoint]) -> (f64, i32) {
+    let mut peak = dollars(opening_balance);
+    let mut peak_date = match equity_curve.first() {
+        Some(p) => p.date().num_days_from_ce(),
+        None => return (0.0, 0),
+    };
+
+    let mut deepest = 0.0f64;
+    let mut longest = 0;
+    let mut below_peak = false;
+
+    for p in equity_curve {
+        let equity = dollars(p.equity());
+        let date = p.date().num_days_from_ce();
+
+        if equity >= peak {
+            if below_peak {
+                longest = longest.max(date - peak_date);
+                below_peak = false;
+            }
+
+            peak = equity;
+            peak_date = date;
+        } else {
+            deepest = deepest.max(ratio(peak - equity, peak));
+            below_peak = true;
+        }
+    }
+
+    // still under water at the end
+    if below_peak {
+        let last = equity_curve.last().unwrap().date().num_days_from_ce();
+        longest = longest.max(last - peak_date);
+    }
+
+    (deepest, longest)
+}
+
+pub fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// of a sample
+pub fn standard_deviation(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+
+    let m = mean(values);
+    let variance =
+        values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+
+    variance.sqrt()
+}
+
+// like the standard deviation, but only counting returns below zero
+fn downside_deviation(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    let squares: f64 = values.iter().map(|v| v.min(0.0).powi(2)).sum();
+
+    (squares / values.len() as f64).sqrt()
+}
+
+fn central_moment(values: &[f64], power: i32) -> f64 {
+    let m = mean(values);
+
+    mean(&values.iter().map(|v| (v - m).powi(power)).collect::<Vec<f64>>())
+}
+
+fn skew(values: &[f64]) -> f64 {
+    ratio(central_moment(values, 3), central_moment(values, 2).powf(1.5))
+}
+
+fn kurtosis(values: &[f64]) -> f64 {
+    let variance = central_moment(values, 2);
+
+    if variance == 0.0 {
+        return 0.0;
+    }
+
+    central_moment(values, 4) / variance.powi(2) - 3.0
+}
+
+pub fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+pub fn dollars(amount: Money) -> f64 {
+    amount.raw_value() as f64 / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::equity_curve_builder::EquityCurveBuilder;
+
+    #[test]
+    fn test_drawdown() {
+        let stats = PerformanceStats::new(
+            Money::new(100, 0),
+            &EquityCurveBuilder::new(&[110, 88, 99, 121, 120])
+                .with_open_positions(&[0, 1, 0, 1, 0])
+                .build(),
+            0.0,
+        );
+
+        assert!((stats.total_return() - 0.2).abs() < 1e-9);
+        // 110 -> 88
+        assert!((stats.max_drawdown() - 0.2).abs() < 1e-9);
+        // the high on the 2nd was made back on the 5th
+        assert_eq!(stats.max_drawdown_days(), 3);
+        assert!((stats.exposure() - 0.4).abs() < 1e-9);
+        assert!(stats.sortino_ratio() > stats.sharpe_ratio());
+    }
+
+    #[test]
+    fn test_monthly_returns() {
+        let mut points = EquityCurveBuilder::new(&[110, 88]).build();
+        points.push(EquityPoint::new(
+            Utc.ymd(2013, 2, 1).and_hms(0, 0, 0),
+            Money::new(132, 0),
+            Money::zero(),
+            Money::zero(),
+            0,
+        ));
+
+        let months = monthly_returns(Money::new(100, 0), &points);
+
+        assert_eq!(months.len(), 2);
+        assert_eq!((months[0].0, months[0].1), (2013, 1));
+        assert!((months[0].2 + 0.12).abs() < 1e-9);
+        assert!((months[1].2 - 0.5).abs() < 1e-9);
+
+        let drawdowns = drawdowns(Money::new(100, 0), &points);
+        assert!((drawdowns[1] + 0.2).abs() < 1e-9);
+        assert_eq!(drawdowns[2], 0.0);
+    }
+
+    #[test]
+    fn test_flat_curve() {
+        let curve = EquityCurveBuilder::new(&[100, 100, 100]).build();
+        let stats = PerformanceStats::new(Money::new(100, 0), &curve, 0.02);
+
+        assert_eq!(stats.annualized_volatility(), 0.0);
+        assert_eq!(stats.sharpe_ratio(), 0.0);
+        assert_eq!(stats.calmar_ratio(), 0.0);
+        assert_eq!(stats.skew(), 0.0);
+    }
+}
@@ -0,0 +1,172 @@
+use std::rc::Rc;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Shares of an underlying.  These only come into existence through exercise
+// and assignment since models trade options, not stock.
+#[derive(Clone)]
+pub struct StockPosition {
+    symbol: Rc<str>,
+    quantity: i32,
+    // what was paid for the shares currently held (negative for a short)
+    cost_basis: Money,
+    realized_profit: Money,
+}
+
+impl StockPosition {
+    pub fn new(symbol: Rc<str>) -> StockPosition {
+        StockPosition {
+            symbol: symbol,
+            quantity: 0,
+            cost_basis: Money::zero(),
+            realized_profit: Money::zero(),
+        }
+    }
+
+    // shares > 0 is a buy and shares < 0 is a sell
+    pub fn apply_trade(&mut self, shares: i32, price: Money) {
+        if shares == 0 {
+            return;
+        }
+
+        let same_direction = self.quantity == 0 || (self.quantity > 0) == (shares > 0);
+
+        if same_direction {
+            self.quantity += shares;
+            self.cost_basis += price * shares;
+            return;
+        }
+
+        // reducing (and maybe flipping) the position: realize against the
+        // average cost of the shares that are being closed out
+        let closing = if shares.abs() > self.quantity.abs() {
+            -self.quantity
+        } else {
+            shares
+        };
+
+        let closed_basis = self.average_basis_for(-closing);
+        self.realized_profit += Money::zero() - price * closing - closed_basis;
+        self.cost_basis -= closed_basis;
+        self.quantity += closing;
+
+        let remaining = shares - closing;
+
+        if remaining != 0 {
+            self.quantity += remaining;
+            self.cost_basis += price * remaining;
+        }
+    }
+
+    // the portion of the cost basis belonging to `shares` of the position
+    fn average_basis_for(&self, shares: i32) -> Money {
+        if self.quantity == 0 {
+            Money::zero()
+        } else {
+            Money::from_cents(
+                (self.cost_basis.raw_value() as i64 * shares as i64 / self.quantity as i64) as i32,
+            )
+        }
+    }
+
+    pub fn symbol(&self) -> Rc<str> {
+        Rc::clone(&self.symbol)
+    }
+
+    pub fn quantity(&self) -> i32 {
+        self.quantity
+    }
+
+    pub fn cost_basis(&self) -> Money {
+        self.cost_basis
+    }
+
+    pub fn realized_profit(&self) -> Money {
+        self.realized_profit
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.quantity != 0
+    }
+
+    pub fn market_value(&self, price: Money) -> Money {
+        price * self.quantity
+    }
+
+    // `to` new shares for every `from` old ones, given the price of an old
+    // share.  the cost basis carries over and any fractional share is paid
+    // out as cash in lieu, which is returned (negative when short).
+    pub fn apply_split(&mut self, to: i32, from: i32, price: Money) -> Money {
+        let total = self.quantity as i64 * to as i64;
+        let remainder = total % from as i64;
+
+        let mut cash_in_lieu = Money::zero();
+
+        if remainder != 0 {
+            cash_in_lieu =
+                Money::from_cents((price.raw_value() as i64 * remainder / to as i64) as i32);
+
+            let basis =
+                Money::from_cents((self.cost_basis.raw_value() as i64 * remainder / total) as i32);
+
+            self.realized_profit += cash_in_lieu - basis;
+            self.cost_basis -= basis;
+        }
+
+        self.quantity = (total / from as i64) as i32;
+
+        cash_in_lieu
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut position = StockPosition::new(Rc::from("AAPL"));
+
+        position.apply_trade(200, Money::new(100, 0));
+        position.apply_trade(-100, Money::new(110, 0));
+
+        assert_eq!(position.quantity(), 100);
+        assert!(position.cost_basis() == Money::new(10_000, 0));
+        assert!(position.realized_profit() == Money::new(1_000, 0));
+        assert!(position.market_value(Money::new(105, 0)) == Money::new(10_500, 0));
+    }
+
+    #[test]
+    fn test_flip_to_short() {
+        let mut position = StockPosition::new(Rc::from("AAPL"));
+
+        position.apply_trade(100, Money::new(100, 0));
+        position.apply_trade(-300, Money::new(90, 0));
+
+        assert_eq!(position.quantity(), -200);
+        assert!(position.realized_profit() == Money::new(-1_000, 0));
+        assert!(position.cost_basis() == Money::new(-18_000, 0));
+
+        position.apply_trade(200, Money::new(80, 0));
+
+        assert!(!position.is_open());
+        assert!(position.realized_profit() == Money::new(1_000, 0));
+    }
+
+    #[test]
+    fn test_reverse_split_pays_cash_in_lieu() {
+        let mut position = StockPosition::new(Rc::from("AAPL"));
+
+        position.apply_trade(100, Money::new(10, 0));
+
+        // 33 new shares @ $30 and a third of a share paid out
+        let cash = position.apply_split(1, 3, Money::new(12, 0));
+
+        assert_eq!(position.quantity(), 33);
+        assert!(cash == Money::new(12, 0));
+        assert!(position.cost_basis() == Money::new(990, 0));
+        assert!(position.realized_profit() == Money::new(2, 0));
+    }
+}
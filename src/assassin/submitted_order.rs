@@ -0,0 +1,108 @@
+use std::rc::Rc;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrderStatus {
+    // accepted but waiting on a trigger
+    Working,
+    Filled,
+    // refused by the broker (not enough buying power, no quote, etc.)
+    Rejected,
+    // never filled: an OCO sibling filled, the position it was closing went
+    // away or a limit wasn't reached
+    Cancelled,
+}
+
+impl OrderStatus {
+    #[allow(dead_code)]
+    pub fn name(&self) -> &'static str {
+        match *self {
+            OrderStatus::Working => "working",
+            OrderStatus::Filled => "filled",
+            OrderStatus::Rejected => "rejected",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+// An order as the broker received it from the model and what became of it.
+// Combos are a single submission for all of their legs.
+#[derive(Clone)]
+pub struct SubmittedOrder {
+    id: u32,
+    date: DateTime<Utc>,
+    symbol: Rc<str>,
+    option_names: Vec<Rc<str>>,
+    summary: String,
+    status: OrderStatus,
+    // when the status last changed
+    status_date: DateTime<Utc>,
+    reason: Option<String>,
+}
+
+impl SubmittedOrder {
+    pub fn new(
+        id: u32,
+        date: DateTime<Utc>,
+        symbol: Rc<str>,
+        option_names: Vec<Rc<str>>,
+        summary: String,
+    ) -> SubmittedOrder {
+        SubmittedOrder {
+            id: id,
+            date: date,
+            symbol: symbol,
+            option_names: option_names,
+            summary: summary,
+            status: OrderStatus::Working,
+            status_date: date,
+            reason: None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: OrderStatus, date: DateTime<Utc>, reason: Option<String>) {
+        self.status = status;
+        self.status_date = date;
+        self.reason = reason;
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    pub fn symbol(&self) -> Rc<str> {
+        Rc::clone(&self.symbol)
+    }
+
+    #[allow(dead_code)]
+    pub fn option_names(&self) -> &Vec<Rc<str>> {
+        &self.option_names
+    }
+
+    // true if the order (or one of its legs) trades this option
+    pub fn is_for(&self, option_name: &str) -> bool {
+        self.option_names.iter().any(|n| &**n == option_name)
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        self.status
+    }
+
+    pub fn status_date(&self) -> DateTime<Utc> {
+        self.status_date
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+}
@@ -0,0 +1,264 @@
+use assassin::json::Json;
+use assassin::stats::PerformanceStats;
+use assassin::trade_stats::TradeStats;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Acceptance criteria a Simulation has to meet to pass, so strategy variants
+// can be screened without reading every report.  Every target is off until
+// it's set, so a Simulation without any always passes.
+#[derive(Clone)]
+pub struct Targets {
+    min_sharpe_ratio: Option<f64>,
+    // see PerformanceStats::max_drawdown().  20.0 is 20%.
+    max_drawdown_percent: Option<f64>,
+    min_trade_count: Option<i32>,
+    // commission over the profit before commission.  0.25 means commission
+    // can't eat more than a quarter of what the trades made.
+    max_commission_to_profit: Option<f64>,
+}
+
+impl Targets {
+    pub fn new() -> Targets {
+        Targets {
+            min_sharpe_ratio: None,
+            max_drawdown_percent: None,
+            min_trade_count: None,
+            max_commission_to_profit: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_sharpe_ratio.is_none()
+            && self.max_drawdown_percent.is_none()
+            && self.min_trade_count.is_none()
+            && self.max_commission_to_profit.is_none()
+    }
+
+    #[allow(dead_code)]
+    pub fn with_min_sharpe_ratio(mut self, sharpe_ratio: f64) -> Targets {
+        self.min_sharpe_ratio = Some(sharpe_ratio);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_max_drawdown_percent(mut self, percent: f64) -> Targets {
+        if percent <= 0.0 || percent > 100.0 {
+            panic!("percent must be > 0.0 and <= 100.0 (got {})", percent);
+        }
+
+        self.max_drawdown_percent = Some(percent);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_min_trade_count(mut self, trades: i32) -> Targets {
+        if trades < 1 {
+            panic!("trades must be >= 1 (got {})", trades);
+        }
+
+        self.min_trade_count = Some(trades);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_max_commission_to_profit(mut self, ratio: f64) -> Targets {
+        if ratio <= 0.0 {
+            panic!("ratio must be > 0.0 (got {})", ratio);
+        }
+
+        self.max_commission_to_profit = Some(ratio);
+        self
+    }
+
+    // profit is net of commission
+    pub fn evaluate(
+        &self,
+        performance: &PerformanceStats,
+        trades: &TradeStats,
+        commission: Money,
+        profit: Money,
+    ) -> Verdict {
+        let mut results = vec![];
+
+        if let Some(min) = self.min_sharpe_ratio {
+            let sharpe_ratio = performance.sharpe_ratio();
+
+            results.push(TargetResult::new(
+                sharpe_ratio >= min,
+                format!("Sharpe ratio of {:.2} (min {:.2})", sharpe_ratio, min),
+            ));
+        }
+
+        if let Some(max) = self.max_drawdown_percent {
+            let drawdown = performance.max_drawdown() * 100.0;
+
+            results.push(TargetResult::new(
+                drawdown <= max,
+                format!("max drawdown of {:.2}% (max {:.2}%)", drawdown, max),
+            ));
+        }
+
+        if let Some(min) = self.min_trade_count {
+            results.push(TargetResult::new(
+                trades.count() >= min,
+                format!("{} trades (min {})", trades.count(), min),
+            ));
+        }
+
+        if let Some(max) = self.max_commission_to_profit {
+            let ratio = commission_to_profit(commission, profit);
+
+            results.push(TargetResult::new(
+                ratio <= max,
+                format!(
+                    "commission of {} is {:.2} of the profit before it (max {:.2})",
+                    commission,
+                    ratio,
+                    max,
+                ),
+            ));
+        }
+
+        Verdict { results: results }
+    }
+}
+
+// infinite when the trades lost money before commission was taken out
+fn commission_to_profit(commission: Money, profit: Money) -> f64 {
+    let gross = profit + commission;
+
+    if commission == Money::zero() {
+        0.0
+    } else if gross <= Money::zero() {
+        f64::INFINITY
+    } else {
+        commission.raw_value() as f64 / gross.raw_value() as f64
+    }
+}
+
+#[derive(Clone)]
+pub struct TargetResult {
+    passed: bool,
+    description: String,
+}
+
+impl TargetResult {
+    fn new(passed: bool, description: String) -> TargetResult {
+        TargetResult {
+            passed: passed,
+            description: description,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("passed", Json::Bool(self.passed)),
+            ("description", Json::string(&self.description)),
+        ])
+    }
+}
+
+// How a Simulation did against its Targets.
+#[derive(Clone)]
+pub struct Verdict {
+    results: Vec<TargetResult>,
+}
+
+impl Verdict {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed())
+    }
+
+    pub fn results(&self) -> &Vec<TargetResult> {
+        &self.results
+    }
+
+    #[allow(dead_code)]
+    pub fn failures(&self) -> Vec<&TargetResult> {
+        self.results.iter().filter(|r| !r.passed()).collect()
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("passed", Json::Bool(self.passed())),
+            ("results", Json::Array(self.results.iter().map(|r| r.to_json()).collect())),
+        ])
+    }
+
+    // for the process to exit with, so scripts can tell a pass from a fail
+    pub fn exit_code(&self) -> i32 {
+        if self.passed() {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use assassin::equity_curve_builder::EquityCurveBuilder;
+
+    fn performance(balances: &[i32]) -> PerformanceStats {
+        let curve = EquityCurveBuilder::new(balances).build();
+        PerformanceStats::new(Money::new(100, 0), &curve, 0.0)
+    }
+
+    #[test]
+    fn test_no_targets_pass() {
+        let verdict = Targets::new().evaluate(
+            &performance(&[50]),
+            &TradeStats::new(&[]),
+            Money::new(10, 0),
+            Money::new(-50, 0),
+        );
+
+        assert!(verdict.passed());
+        assert_eq!(verdict.exit_code(), 0);
+        assert!(Targets::new().is_empty());
+        assert!(!Targets::new().with_min_trade_count(1).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let targets = Targets::new()
+            .with_max_drawdown_percent(25.0)
+            .with_min_trade_count(1)
+            .with_max_commission_to_profit(0.25);
+
+        // 110 -> 88 is a 20% drawdown, and $5 of the $25 made before
+        // commission went to it
+        let verdict = targets.evaluate(
+            &performance(&[110, 88, 120]),
+            &TradeStats::new(&[]),
+            Money::new(5, 0),
+            Money::new(20, 0),
+        );
+
+        assert!(!verdict.passed());
+        assert_eq!(verdict.exit_code(), 1);
+        assert_eq!(verdict.results().len(), 3);
+        assert_eq!(verdict.failures().len(), 1);
+        assert_eq!(verdict.failures()[0].description(), "0 trades (min 1)");
+    }
+
+    #[test]
+    fn test_commission_to_profit() {
+        assert_eq!(commission_to_profit(Money::new(5, 0), Money::new(15, 0)), 0.25);
+        assert_eq!(commission_to_profit(Money::zero(), Money::new(-15, 0)), 0.0);
+        assert!(commission_to_profit(Money::new(5, 0), Money::new(-15, 0)).is_infinite());
+    }
+}
@@ -0,0 +1,516 @@
+// A single-file HTML report of a finished Simulation, to share a backtest by
+// sending one file.  The charts are inline SVG and the styles are inline, so
+// it doesn't need anything else to display.
+
+use std::fs::File;
+use std::io;
+use std::io::Write;
+
+use assassin::backtest_result::BacktestResult;
+use assassin::returns_table::MONTHS;
+use assassin::stats;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+static CHART_WIDTH: f64 = 800.0;
+static CHART_HEIGHT: f64 = 200.0;
+// room on the left for the value labels
+static CHART_LEFT: f64 = 90.0;
+static CHART_PADDING: f64 = 10.0;
+
+static STYLE: &str = "
+body { font-family: -apple-system, Helvetica, Arial, sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { border-bottom: 1px solid #ccc; margin-top: 2em; }
+table { border-collapse: collapse; margin: 0.5em 0 1em; font-size: 0.9em; }
+th, td { padding: 0.25em 0.75em; border: 1px solid #ddd; text-align: right; }
+th { background: #f4f4f4; }
+td.name, th.name { text-align: left; }
+.neg { color: #c00; }
+.pass { color: #080; font-weight: bold; }
+.fail { color: #c00; font-weight: bold; }
+.axis { color: #888; font-size: 0.8em; margin: 0 0 1em; }
+";
+
+pub fn write(result: &BacktestResult, filename: &str) -> io::Result<()> {
+    let mut file = File::create(filename)?;
+
+    write!(file, "{}", render(result))
+}
+
+pub fn render(result: &BacktestResult) -> String {
+    let mut html = String::new();
+    let title = escape(&result.models().join(", "));
+
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", title));
+    html.push_str(&format!("<style>{}</style>\n", STYLE));
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", title));
+
+    html.push_str(&config(result));
+    html.push_str(&summary(result));
+    html.push_str(&benchmark(result));
+    html.push_str(&charts(result));
+    html.push_str(&monthly_returns(result));
+    html.push_str(&costs(result));
+    html.push_str(&margin(result));
+    html.push_str(&trades(result));
+    html.push_str(&positions(result));
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// ===== sections =======================================================================
+
+fn config(result: &BacktestResult) -> String {
+    let curve = result.equity_curve();
+    let date = |i: Option<usize>| match i.and_then(|i| curve.get(i)) {
+        Some(p) => p.date().format("%Y-%m-%d").to_string(),
+        None => "-".to_string(),
+    };
+
+    let mut html = "<h2>Config</h2>\n".to_string();
+
+    html.push_str(&table(&[
+        ("Models", escape(&result.models().join(", "))),
+        ("First day", date(Some(0))),
+        ("Last day", date(curve.len().checked_sub(1))),
+        ("Starting balance", result.starting_balance().to_string()),
+        ("Risk free rate", percent(result.risk_free_rate())),
+        ("Margin", result.margin_calculator().to_string()),
+        ("Positions marked at", result.mark_method().to_string()),
+    ]));
+
+    html
+}
+
+fn summary(result: &BacktestResult) -> String {
+    let stats = result.performance();
+    let verdict = result.verdict();
+
+    let mut html = "<h2>Summary</h2>\n".to_string();
+
+    html.push_str(&table(&[
+        ("Ending balance", money(result.ending_balance())),
+        ("Profit", money(result.profit())),
+        ("Total return", percent(stats.total_return())),
+        ("Annualized return", percent(stats.annualized_return())),
+        ("Annualized volatility", percent(stats.annualized_volatility())),
+        ("Sharpe ratio", format!("{:.2}", stats.sharpe_ratio())),
+        ("Sortino ratio", format!("{:.2}", stats.sortino_ratio())),
+        ("Max drawdown", percent(stats.max_drawdown())),
+        ("Longest drawdown", format!("{} days", stats.max_drawdown_days())),
+        ("Calmar ratio", format!("{:.2}", stats.calmar_ratio())),
+        ("Exposure", percent(stats.exposure())),
+    ]));
+
+    if !verdict.results().is_empty() {
+        html.push_str("<h3>Targets</h3>\n<table>\n");
+
+        for r in verdict.results() {
+            html.push_str(&format!(
+                "<tr><td class=\"{}\">{}</td><td class=\"name\">{}</td></tr>\n",
+                pass_or_fail(r.passed()),
+                pass_or_fail(r.passed()).to_uppercase(),
+                escape(r.description()),
+            ));
+        }
+
+        html.push_str("</table>\n");
+        html.push_str(&format!(
+            "<p>Verdict: <span class=\"{}\">{}</span></p>\n",
+            pass_or_fail(verdict.passed()),
+            pass_or_fail(verdict.passed()).to_uppercase(),
+        ));
+    }
+
+    html
+}
+
+fn benchmark(result: &BacktestResult) -> String {
+    let benchmark = match result.benchmark() {
+        Some(b) => b,
+        None => return String::new(),
+    };
+
+    let mut html = format!("<h2>Benchmark: {}</h2>\n", escape(benchmark.name()));
+
+    html.push_str(&table(&[
+        ("Total return", percent(benchmark.performance().total_return())),
+        ("Annualized return", percent(benchmark.performance().annualized_return())),
+        ("Max drawdown", percent(benchmark.performance().max_drawdown())),
+        ("Alpha", percent(benchmark.alpha())),
+        ("Beta", format!("{:.2}", benchmark.beta())),
+        ("Correlation", format!("{:.2}", benchmark.correlation())),
+        ("Tracking error", percent(benchmark.tracking_error())),
+        ("Information ratio", format!("{:.2}", benchmark.information_ratio())),
+        ("Up capture", percent(benchmark.up_capture())),
+        ("Down capture", percent(benchmark.down_capture())),
+    ]));
+
+    html
+}
+
+fn charts(result: &BacktestResult) -> String {
+    let curve = result.equity_curve();
+
+    let equity: Vec<f64> = curve.iter().map(|p| stats::dollars(p.equity())).collect();
+    let drawdowns = stats::drawdowns(result.starting_balance(), curve);
+
+    let mut html = "<h2>Equity</h2>\n".to_string();
+
+    let low = equity.iter().cloned().fold(f64::INFINITY, f64::min);
+    html.push_str(&chart(&equity, low, "#0366d6", &|v| format!("${:.0}", v)));
+    html.push_str(&axis(result));
+
+    html.push_str("<h2>Drawdown</h2>\n");
+    html.push_str(&chart(&drawdowns, 0.0, "#c00", &|v| percent(v)));
+    html.push_str(&axis(result));
+
+    html
+}
+
+fn monthly_returns(result: &BacktestResult) -> String {
+    let returns = result.returns();
+
+    let mut html = "<h2>Monthly returns</h2>\n<table>\n<tr><th>Year</th>".to_string();
+
+    for m in MONTHS.iter() {
+        html.push_str(&format!("<th>{}</th>", m));
+    }
+
+    html.push_str("<th>Year</th></tr>\n");
+
+    for &(year, yearly) in returns.years() {
+        html.push_str(&format!("<tr><th>{}</th>", year));
+
+        for month in 1..13 {
+            match returns.month(year, month) {
+                Some(r) => html.push_str(&heat_cell(r)),
+                None => html.push_str("<td></td>"),
+            }
+        }
+
+        html.push_str(&heat_cell(yearly));
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+
+    let month = |m: Option<(i32, u32, f64)>| match m {
+        Some((year, month, r)) => {
+            format!("{} {} ({})", MONTHS[month as usize - 1], year, percent(r))
+        }
+        None => "-".to_string(),
+    };
+
+    html.push_str(&table(&[
+        ("Best month", month(returns.best_month())),
+        ("Worst month", month(returns.worst_month())),
+        ("Positive months", percent(returns.positive_months())),
+    ]));
+
+    html
+}
+
+fn costs(result: &BacktestResult) -> String {
+    let trades = result.trade_stats();
+
+    let per_trade = if trades.count() > 0 {
+        result.commission() / trades.count()
+    } else {
+        Money::zero()
+    };
+
+    let mut html = "<h2>Costs</h2>\n".to_string();
+
+    html.push_str(&table(&[
+        ("Commission", result.commission().to_string()),
+        ("Commission per trade", per_trade.to_string()),
+        ("Slippage from the midpoint", money(result.slippage())),
+        ("Interest (net)", money(result.interest())),
+        ("Dividends", result.dividends().to_string()),
+    ]));
+
+    html
+}
+
+// the enforced calculator and any it was compared with
+fn margin(result: &BacktestResult) -> String {
+    let mut html = "<h2>Margin</h2>\n".to_string();
+
+    html.push_str(
+        "<table>\n<tr><th class=\"name\">Calculator</th><th>Highest requirement</th>\
+         <th>Lowest buying power</th><th>Return on requirement</th></tr>\n",
+    );
+
+    for m in result.margin() {
+        let name = if m.is_enforced() {
+            m.calculator().to_string()
+        } else {
+            format!("{} (not enforced)", m.calculator())
+        };
+
+        html.push_str(&format!(
+            "<tr><td class=\"name\">{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&name),
+            m.highest_requirement(),
+            money(m.lowest_buying_power()),
+            percent(m.return_on_requirement(result.profit())),
+        ));
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+fn trades(result: &BacktestResult) -> String {
+    let stats = result.trade_stats();
+
+    let mut html = "<h2>Trades</h2>\n".to_string();
+
+    html.push_str(&table(&[
+        ("Trades", stats.count().to_string()),
+        ("Win rate", percent(stats.win_rate())),
+        ("Average winner", money(stats.average_winner())),
+        ("Average loser", money(stats.average_loser())),
+        ("Largest winner", money(stats.largest_winner())),
+        ("Largest loser", money(stats.largest_loser())),
+        ("Profit factor", format!("{:.2}", stats.profit_factor())),
+        ("Expectancy", money(stats.expectancy())),
+        ("Average days held", format!("{:.1}", stats.average_days_held())),
+        ("Closed by the model", stats.closed_by_model().to_string()),
+        ("Closed by the broker", stats.closed_by_broker().to_string()),
+    ]));
+
+    html.push_str(
+        "<table>\n<tr><th>Opened</th><th>Closed</th><th>Days</th>\
+         <th class=\"name\">Option</th><th class=\"name\">Group</th><th>Side</th>\
+         <th>Contracts</th><th>Profit</th><th>MAE</th><th>MFE</th><th>Closed by</th></tr>\n",
+    );
+
+    for t in result.trades() {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"name\">{}</td>\
+             <td class=\"name\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+             <td>{}</td><td>{}</td></tr>\n",
+            t.opened().format("%Y-%m-%d"),
+            t.closed().format("%Y-%m-%d"),
+            t.days_held(),
+            escape(&t.option_name()),
+            escape(&t.group().map(|g| g.to_string()).unwrap_or(String::new())),
+            if t.is_long() { "long" } else { "short" },
+            t.contracts(),
+            money(t.profit()),
+            money(t.max_adverse_excursion()),
+            money(t.max_favorable_excursion()),
+            if t.closed_by_broker() { "broker" } else { "model" },
+        ));
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+// every fill of every position, like print_stats logs them
+fn positions(result: &BacktestResult) -> String {
+    let mut html = "<h2>Positions</h2>\n".to_string();
+
+    for p in result.positions() {
+        let group = match p.group() {
+            Some(g) => format!(" ({})", g),
+            None => String::new(),
+        };
+
+        html.push_str(&format!("<h3>{}{}</h3>\n", escape(&p.name()), escape(&group)));
+        html.push_str(
+            "<table>\n<tr><th>Date</th><th class=\"name\">Action</th><th>Contracts</th>\
+             <th>Price</th><th>Commission</th><th>Cash</th></tr>\n",
+        );
+
+        for o in p.orders() {
+            let action = if o.closed_by_broker() {
+                format!("{} (broker)", o.buy_or_sell_string())
+            } else {
+                o.buy_or_sell_string().to_string()
+            };
+
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"name\">{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td>{}</td></tr>\n",
+                o.fill_date().format("%Y-%m-%d"),
+                action,
+                o.quantity(),
+                o.fill_price(),
+                o.commission(),
+                money(o.canonical_cost_basis()),
+            ));
+        }
+
+        html.push_str(&format!(
+            "<tr><th class=\"name\" colspan=\"5\">Realized, net of commission</th>\
+             <th>{}</th></tr>\n</table>\n",
+            money(p.realized_profit() - p.commission_paid()),
+        ));
+    }
+
+    html
+}
+
+// ===== helpers ========================================================================
+
+// a line through the values, scaled to fit, with the area between it and the
+// baseline shaded
+fn chart(values: &[f64], baseline: f64, color: &str, label: &Fn(f64) -> String) -> String {
+    if values.len() < 2 {
+        return "<p>Not enough trading days to chart.</p>\n".to_string();
+    }
+
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let range = if max > min { max - min } else { 1.0 };
+
+    let plot_width = CHART_WIDTH - CHART_LEFT - CHART_PADDING;
+
+    let x = |i: usize| CHART_LEFT + i as f64 / (values.len() - 1) as f64 * plot_width;
+    let y = |v: f64| CHART_PADDING + (max - v) / range * (CHART_HEIGHT - 2.0 * CHART_PADDING);
+
+    let line: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| format!("{:.1},{:.1}", x(i), y(*v)))
+        .collect();
+    let line = line.join(" ");
+
+    let base = y(baseline.max(min).min(max));
+    let area = format!(
+        "{:.1},{:.1} {} {:.1},{:.1}",
+        x(0),
+        base,
+        line,
+        x(values.len() - 1),
+        base,
+    );
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n\
+         <rect x=\"{l}\" y=\"0\" width=\"{cw}\" height=\"{h}\" fill=\"#fafafa\"/>\n\
+         <polygon points=\"{area}\" fill=\"{color}\" fill-opacity=\"0.15\"/>\n\
+         <polyline points=\"{line}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"1.5\"/>\n\
+         <text x=\"{tx}\" y=\"{top}\" text-anchor=\"end\" font-size=\"12\">{max}</text>\n\
+         <text x=\"{tx}\" y=\"{bottom}\" text-anchor=\"end\" font-size=\"12\">{min}</text>\n\
+         </svg>\n",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT,
+        l = CHART_LEFT,
+        cw = CHART_WIDTH - CHART_LEFT,
+        area = area,
+        line = line,
+        color = color,
+        tx = CHART_LEFT - 6.0,
+        top = CHART_PADDING + 4.0,
+        bottom = CHART_HEIGHT - CHART_PADDING,
+        max = escape(&label(max)),
+        min = escape(&label(min)),
+    )
+}
+
+// the first and last day under a chart
+fn axis(result: &BacktestResult) -> String {
+    let curve = result.equity_curve();
+
+    match (curve.first(), curve.last()) {
+        (Some(first), Some(last)) => format!(
+            "<p class=\"axis\">{} to {}</p>\n",
+            first.date().format("%Y-%m-%d"),
+            last.date().format("%Y-%m-%d"),
+        ),
+        _ => String::new(),
+    }
+}
+
+// a two column table of names and values
+fn table(rows: &[(&str, String)]) -> String {
+    let mut html = "<table>\n".to_string();
+
+    for &(name, ref value) in rows {
+        html.push_str(&format!("<tr><th class=\"name\">{}</th><td>{}</td></tr>\n", name, value));
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+fn heat_cell(r: f64) -> String {
+    format!("<td style=\"background: {}\">{}</td>", heat(r), percent(r))
+}
+
+// green for gains and red for losses, darker the bigger they are (10% a month
+// is as dark as it gets)
+fn heat(r: f64) -> String {
+    let alpha = 0.1 + (r.abs() / 0.10).min(1.0) * 0.6;
+
+    if r >= 0.0 {
+        format!("rgba(0, 160, 0, {:.2})", alpha)
+    } else {
+        format!("rgba(200, 0, 0, {:.2})", alpha)
+    }
+}
+
+// losses in red
+fn money(amount: Money) -> String {
+    if amount < Money::zero() {
+        format!("<span class=\"neg\">{}</span>", amount)
+    } else {
+        amount.to_string()
+    }
+}
+
+// 0.1 is 10.00%
+fn percent(fraction: f64) -> String {
+    format!("{:.2}%", fraction * 100.0)
+}
+
+fn pass_or_fail(passed: bool) -> &'static str {
+    if passed {
+        "pass"
+    } else {
+        "fail"
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_chart() {
+        let svg = chart(&[100.0, 110.0, 90.0], 90.0, "red", &|v| format!("<{}>", v));
+
+        // the high at the top and the low at the bottom
+        assert!(svg.contains("<polyline points=\"90.0,100.0 440.0,10.0 790.0,190.0\""));
+        assert!(svg.contains(">&lt;110&gt;</text>"));
+        assert!(svg.contains(">&lt;90&gt;</text>"));
+
+        assert!(chart(&[100.0], 0.0, "red", &|v| v.to_string()).starts_with("<p>"));
+    }
+
+    #[test]
+    fn test_heat() {
+        assert_eq!(heat(0.2), "rgba(0, 160, 0, 0.70)");
+        assert_eq!(heat(-0.05), "rgba(200, 0, 0, 0.40)");
+    }
+}
@@ -0,0 +1,159 @@
+use std::rc::Rc;
+
+use assassin::csv::{decimal, quoted};
+use assassin::json::Json;
+use assassin::trade_group::TradeGroup;
+
+extern crate chrono;
+use self::chrono::prelude::*;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// A round trip in a single Position: from flat, through however many fills,
+// back to flat.
+#[derive(Clone)]
+pub struct Trade {
+    option_name: Rc<str>,
+    group: Option<TradeGroup>,
+    opened: DateTime<Utc>,
+    closed: DateTime<Utc>,
+    // of the fill that opened it
+    long: bool,
+    // the most held at once
+    contracts: i32,
+    // net of commission
+    profit: Money,
+    commission: Money,
+    // the worst (<= 0) and best (>= 0) the trade was marked at the close of a
+    // trading day while it was open
+    max_adverse_excursion: Money,
+    max_favorable_excursion: Money,
+    // by the broker (expiration, assignment, the end of the simulation)
+    // instead of the model
+    closed_by_broker: bool,
+}
+
+impl Trade {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        option_name: Rc<str>,
+        group: Option<TradeGroup>,
+        opened: DateTime<Utc>,
+        closed: DateTime<Utc>,
+        long: bool,
+        contracts: i32,
+        profit: Money,
+        commission: Money,
+        max_adverse_excursion: Money,
+        max_favorable_excursion: Money,
+        closed_by_broker: bool,
+    ) -> Trade {
+        Trade {
+            option_name: option_name,
+            group: group,
+            opened: opened,
+            closed: closed,
+            long: long,
+            contracts: contracts,
+            profit: profit,
+            commission: commission,
+            max_adverse_excursion: max_adverse_excursion,
+            max_favorable_excursion: max_favorable_excursion,
+            closed_by_broker: closed_by_broker,
+        }
+    }
+
+    pub fn option_name(&self) -> Rc<str> {
+        Rc::clone(&self.option_name)
+    }
+
+    pub fn group(&self) -> Option<TradeGroup> {
+        self.group.clone()
+    }
+
+    pub fn opened(&self) -> DateTime<Utc> {
+        self.opened
+    }
+
+    pub fn closed(&self) -> DateTime<Utc> {
+        self.closed
+    }
+
+    pub fn is_long(&self) -> bool {
+        self.long
+    }
+
+    pub fn contracts(&self) -> i32 {
+        self.contracts
+    }
+
+    pub fn profit(&self) -> Money {
+        self.profit
+    }
+
+    #[allow(dead_code)]
+    pub fn commission(&self) -> Money {
+        self.commission
+    }
+
+    pub fn is_winner(&self) -> bool {
+        self.profit > Money::zero()
+    }
+
+    pub fn days_held(&self) -> i32 {
+        self.closed.num_days_from_ce() - self.opened.num_days_from_ce()
+    }
+
+    pub fn max_adverse_excursion(&self) -> Money {
+        self.max_adverse_excursion
+    }
+
+    pub fn max_favorable_excursion(&self) -> Money {
+        self.max_favorable_excursion
+    }
+
+    pub fn closed_by_broker(&self) -> bool {
+        self.closed_by_broker
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("option_name", Json::string(&self.option_name)),
+            ("group", Json::optional(self.group.as_ref(), |g| Json::String(g.to_string()))),
+            ("opened", Json::date(self.opened)),
+            ("closed", Json::date(self.closed)),
+            ("days_held", Json::number(self.days_held())),
+            ("long", Json::Bool(self.long)),
+            ("contracts", Json::number(self.contracts)),
+            ("profit", Json::Money(self.profit)),
+            ("commission", Json::Money(self.commission)),
+            ("max_adverse_excursion", Json::Money(self.max_adverse_excursion)),
+            ("max_favorable_excursion", Json::Money(self.max_favorable_excursion)),
+            ("closed_by_broker", Json::Bool(self.closed_by_broker)),
+        ])
+    }
+
+    pub fn csv_header() -> &'static str {
+        "option_name,group,opened,closed,days_held,long,contracts,profit,commission,\
+         max_adverse_excursion,max_favorable_excursion,closed_by_broker"
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            quoted(&self.option_name),
+            quoted(&self.group.as_ref().map(|g| g.to_string()).unwrap_or_default()),
+            self.opened.format("%Y-%m-%d"),
+            self.closed.format("%Y-%m-%d"),
+            self.days_held(),
+            self.long,
+            self.contracts,
+            decimal(self.profit),
+            decimal(self.commission),
+            decimal(self.max_adverse_excursion),
+            decimal(self.max_favorable_excursion),
+            self.closed_by_broker,
+        )
+    }
+}
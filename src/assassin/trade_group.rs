@@ -0,0 +1,36 @@
+use std::fmt;
+use std::rc::Rc;
+
+// Tags the orders (and so the positions) that make up one trade of a
+// strategy, e.g. both legs of a diagonal.  Positions in different groups are
+// tracked separately even when they're in the same option.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct TradeGroup {
+    strategy: Rc<str>,
+    // chosen by the strategy, unique within it
+    id: u32,
+}
+
+impl TradeGroup {
+    pub fn new(strategy: &str, id: u32) -> TradeGroup {
+        TradeGroup {
+            strategy: Rc::from(strategy),
+            id: id,
+        }
+    }
+
+    pub fn strategy(&self) -> Rc<str> {
+        Rc::clone(&self.strategy)
+    }
+
+    #[allow(dead_code)]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl fmt::Display for TradeGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} #{}", self.strategy, self.id)
+    }
+}
@@ -0,0 +1,223 @@
+use assassin::json::Json;
+use assassin::trade::Trade;
+
+extern crate greenback;
+use greenback::Greenback as Money;
+
+// Statistics over a set of closed Trades.  Trades that broke even count as
+// losers.
+#[derive(Clone, Copy)]
+pub struct TradeStats {
+    count: i32,
+    winners: i32,
+    losers: i32,
+    gross_profit: Money,
+    // <= 0
+    gross_loss: Money,
+    largest_winner: Money,
+    largest_loser: Money,
+    total_days_held: i32,
+    total_adverse_excursion: Money,
+    total_favorable_excursion: Money,
+    worst_adverse_excursion: Money,
+    closed_by_broker: i32,
+}
+
+impl TradeStats {
+    pub fn new(trades: &[Trade]) -> TradeStats {
+        let winners: Vec<&Trade> = trades.iter().filter(|t| t.is_winner()).collect();
+        let losers: Vec<&Trade> = trades.iter().filter(|t| !t.is_winner()).collect();
+
+        TradeStats {
+            count: trades.len() as i32,
+            winners: winners.len() as i32,
+            losers: losers.len() as i32,
+            gross_profit: winners.iter().map(|t| t.profit()).sum(),
+            gross_loss: losers.iter().map(|t| t.profit()).sum(),
+            largest_winner: winners.iter().map(|t| t.profit()).max().unwrap_or(Money::zero()),
+            largest_loser: losers.iter().map(|t| t.profit()).min().unwrap_or(Money::zero()),
+            total_days_held: trades.iter().map(|t| t.days_held()).sum(),
+            total_adverse_excursion: trades.iter().map(|t| t.max_adverse_excursion()).sum(),
+            total_favorable_excursion: trades.iter().map(|t| t.max_favorable_excursion()).sum(),
+            worst_adverse_excursion: trades
+                .iter()
+                .map(|t| t.max_adverse_excursion())
+                .min()
+                .unwrap_or(Money::zero()),
+            closed_by_broker: trades.iter().filter(|t| t.closed_by_broker()).count() as i32,
+        }
+    }
+
+    pub fn count(&self) -> i32 {
+        self.count
+    }
+
+    pub fn winners(&self) -> i32 {
+        self.winners
+    }
+
+    pub fn losers(&self) -> i32 {
+        self.losers
+    }
+
+    // 0.6 is 60%
+    pub fn win_rate(&self) -> f64 {
+        fraction(self.winners, self.count)
+    }
+
+    pub fn loss_rate(&self) -> f64 {
+        fraction(self.losers, self.count)
+    }
+
+    pub fn average_winner(&self) -> Money {
+        average(self.gross_profit, self.winners)
+    }
+
+    pub fn average_loser(&self) -> Money {
+        average(self.gross_loss, self.losers)
+    }
+
+    pub fn largest_winner(&self) -> Money {
+        self.largest_winner
+    }
+
+    pub fn largest_loser(&self) -> Money {
+        self.largest_loser
+    }
+
+    // gross profit over gross loss.  infinite when nothing lost money.
+    pub fn profit_factor(&self) -> f64 {
+        if self.gross_loss == Money::zero() {
+            if self.gross_profit > Money::zero() {
+                return f64::INFINITY;
+            }
+
+            return 0.0;
+        }
+
+        self.gross_profit.raw_value() as f64 / -self.gross_loss.raw_value() as f64
+    }
+
+    // the average profit per trade
+    pub fn expectancy(&self) -> Money {
+        average(self.gross_profit + self.gross_loss, self.count)
+    }
+
+    pub fn average_days_held(&self) -> f64 {
+        fraction(self.total_days_held, self.count)
+    }
+
+    pub fn average_adverse_excursion(&self) -> Money {
+        average(self.total_adverse_excursion, self.count)
+    }
+
+    pub fn average_favorable_excursion(&self) -> Money {
+        average(self.total_favorable_excursion, self.count)
+    }
+
+    pub fn worst_adverse_excursion(&self) -> Money {
+        self.worst_adverse_excursion
+    }
+
+    pub fn closed_by_broker(&self) -> i32 {
+        self.closed_by_broker
+    }
+
+    pub fn closed_by_model(&self) -> i32 {
+        self.count - self.closed_by_broker
+    }
+
+    pub fn to_json(self) -> Json {
+        Json::Object(vec![
+            ("count", Json::number(self.count)),
+            ("winners", Json::number(self.winners)),
+            ("losers", Json::number(self.losers)),
+            ("win_rate", Json::Number(self.win_rate())),
+            ("average_winner", Json::Money(self.average_winner())),
+            ("average_loser", Json::Money(self.average_loser())),
+            ("largest_winner", Json::Money(self.largest_winner)),
+            ("largest_loser", Json::Money(self.largest_loser)),
+            ("profit_factor", Json::Number(self.profit_factor())),
+            ("expectancy", Json::Money(self.expectancy())),
+            ("average_days_held", Json::Number(self.average_days_held())),
+            ("average_adverse_excursion", Json::Money(self.average_adverse_excursion())),
+            ("worst_adverse_excursion", Json::Money(self.worst_adverse_excursion)),
+            ("average_favorable_excursion", Json::Money(self.average_favorable_excursion())),
+            ("closed_by_model", Json::number(self.closed_by_model())),
+            ("closed_by_broker", Json::number(self.closed_by_broker)),
+        ])
+    }
+}
+
+fn fraction(part: i32, whole: i32) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+fn average(total: Money, count: i32) -> Money {
+    if count == 0 {
+        Money::zero()
+    } else {
+        total / count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use std::rc::Rc;
+
+    extern crate chrono;
+    use self::chrono::prelude::*;
+
+    fn trade(profit: i32, days: u32, mae: i32, closed_by_broker: bool) -> Trade {
+        Trade::new(
+            Rc::from("AAPL"),
+            None,
+            Utc.ymd(2013, 1, 2).and_hms(0, 0, 0),
+            Utc.ymd(2013, 1, 2 + days).and_hms(0, 0, 0),
+            true,
+            1,
+            Money::new(profit, 0),
+            Money::new(1, 0),
+            Money::new(mae, 0),
+            Money::new(profit.max(0), 0),
+            closed_by_broker,
+        )
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = TradeStats::new(&[
+            trade(300, 10, -50, false),
+            trade(-100, 4, -150, true),
+            trade(100, 1, 0, false),
+            trade(-200, 5, -200, false),
+        ]);
+
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.win_rate(), 0.5);
+        assert!(stats.average_winner() == Money::new(200, 0));
+        assert!(stats.average_loser() == Money::new(-150, 0));
+        assert!(stats.largest_loser() == Money::new(-200, 0));
+        assert!((stats.profit_factor() - 400.0 / 300.0).abs() < 1e-9);
+        assert!(stats.expectancy() == Money::new(25, 0));
+        assert_eq!(stats.average_days_held(), 5.0);
+        assert!(stats.worst_adverse_excursion() == Money::new(-200, 0));
+        assert_eq!(stats.closed_by_model(), 3);
+    }
+
+    #[test]
+    fn test_no_losers() {
+        let stats = TradeStats::new(&[trade(100, 1, 0, false)]);
+
+        assert!(stats.profit_factor().is_infinite());
+        assert!(stats.average_loser() == Money::zero());
+        assert!(stats.largest_loser() == Money::zero());
+    }
+}
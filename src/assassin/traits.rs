@@ -1,13 +1,39 @@
 use assassin::broker::Broker;
+use assassin::exercise::ExerciseEvent;
 use assassin::filled_order::FilledOrder;
-use assassin::order::Order;
+use assassin::holding::Holding;
+use assassin::margin_requirement::MarginRequirement;
+use assassin::order_request::OrderRequest;
 use assassin::quote::Quote;
 
+extern crate chrono;
+use self::chrono::prelude::*;
+
 extern crate greenback;
 use greenback::Greenback as Money;
 
 pub trait Commission {
     fn commission_for(&self, &FilledOrder) -> Money;
+    // total commission for all legs of a combo order filled as a single ticket
+    fn commission_for_combo(&self, legs: &[FilledOrder]) -> Money {
+        legs.iter().map(|l| self.commission_for(l)).sum()
+    }
+}
+
+pub trait EarlyAssignment {
+    // called for short, in-the-money options.  the dividend is the per-share
+    // amount going ex before the next trading day, if any.
+    fn should_assign(&self, quote: &Quote, dividend: Option<Money>) -> bool;
+}
+
+pub trait MarginCalculator {
+    fn name(&self) -> &'static str;
+    fn requirement_for(&self, holdings: &[Holding]) -> MarginRequirement;
+}
+
+pub trait InterestRate {
+    // 0.05 is 5% a year
+    fn annual_rate_on(&self, date: DateTime<Utc>) -> f64;
 }
 
 pub trait DataFeed {
@@ -18,7 +44,9 @@ pub trait Model {
     fn name(&self) -> &'static str;
     fn before_simulation(&mut self, &Broker);
     fn after_simulation(&mut self, &Broker);
-    fn run_logic(&mut self, &Broker) -> Vec<Order>;
+    fn run_logic(&mut self, &Broker) -> Vec<OrderRequest>;
+    // exercise, assignment and expiration of the model's options
+    fn handle_exercise_event(&mut self, _broker: &Broker, _event: &ExerciseEvent) {}
     fn show_bod_header(&self, &Broker);
     fn show_eod_summary(&self, &Broker);
 }
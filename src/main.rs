@@ -1,8 +1,17 @@
+// trait objects are written without `dyn`, which older 2015-edition compilers
+// don't accept
+#![allow(bare_trait_objects)]
+
+use std::fs;
+use std::path::Path;
+
 mod assassin;
 use assassin::simulation::Simulation;
+use assassin::benchmark::Benchmark;
 use assassin::feeds::discount_option_data::DiscountOptionData;
 use assassin::models::pmcc::PMCC;
 use assassin::broker::Broker;
+use assassin::margin::portfolio::PortfolioMargin;
 use assassin::commission::charles_schwab::CharlesSchwab;
 
 extern crate env_logger;
@@ -13,23 +22,72 @@ extern crate log;
 extern crate greenback;
 use greenback::Greenback as Money;
 
-static INPUT_FILE: &'static str = "/Users/billrobinson/Desktop/aapl_2013.csv";
+static INPUT_FILE: &str = "/Users/billrobinson/Desktop/aapl_2013.csv";
+static RESULT_FILE: &str = "backtest_result.json";
+static CSV_DIRECTORY: &str = "backtest_csv";
+static TEAR_SHEET_FILE: &str = "tear_sheet.html";
 
 fn main() {
     env_logger::init().unwrap();
 
+    // where the results are written.  nothing is written without one.
+    let output_directory = std::env::args().nth(1);
+
     let starting_capital = Money::new(100_000, 0);
     let feed = DiscountOptionData::new(INPUT_FILE);
     let test_model = PMCC::new();
 
     let commission = CharlesSchwab::new();
 
-    let broker = Broker::new(starting_capital, Box::new(commission), Box::new(feed));
+    let mut broker = Broker::new(starting_capital, Box::new(commission), Box::new(feed));
+    broker.add_comparison_margin_calculator(Box::new(PortfolioMargin::new()));
 
     let mut simulation = Simulation::new(Box::new(test_model), Box::new(broker));
 
+    // an index to compare the run to instead of buying and holding
+    if let Some(filename) = std::env::args().nth(2) {
+        match Benchmark::from_csv("benchmark", &filename) {
+            Ok(benchmark) => simulation.set_benchmark(benchmark),
+            Err(e) => {
+                error!("Couldn't read the benchmark: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     info!("Starting simulation with {}", starting_capital);
     simulation.run();
 
     simulation.print_stats();
+
+    if let Some(ref directory) = output_directory {
+        let directory = Path::new(directory);
+
+        if let Err(e) = fs::create_dir_all(directory) {
+            error!("Couldn't create {}: {}", directory.display(), e);
+        }
+
+        let result_file = directory.join(RESULT_FILE);
+
+        if let Err(e) = simulation.result().write_json(&result_file.to_string_lossy()) {
+            error!("Couldn't write {}: {}", result_file.display(), e);
+        }
+
+        let csv_directory = directory.join(CSV_DIRECTORY);
+
+        if let Err(e) = simulation.write_csv(&csv_directory.to_string_lossy()) {
+            error!("Couldn't write {}: {}", csv_directory.display(), e);
+        }
+
+        let tear_sheet_file = directory.join(TEAR_SHEET_FILE);
+
+        if let Err(e) = simulation.write_tear_sheet(&tear_sheet_file.to_string_lossy()) {
+            error!("Couldn't write {}: {}", tear_sheet_file.display(), e);
+        }
+    }
+
+    // without targets there's nothing to pass or fail
+    if simulation.has_targets() {
+        std::process::exit(simulation.verdict().exit_code());
+    }
 }